@@ -17,22 +17,13 @@ use Color::Rgb;
 fn main() {
     io::stdout().execute(terminal::SetTitle("Frames Demo!")).unwrap();
 
+    let _session = session::craft().done().unwrap();
+
     let mut manager = LayoutManager::new().unwrap();
 
     let mut main_page = MainPage::new(&mut manager);
 
-    main_page.main(&mut manager);
-}
-
-struct MenuUpdate {}
-
-impl position::SizeUpdate for MenuUpdate {
-    fn size_update(&mut self, pos: &mut position::PosData, new_size: Coord) {
-        pos.pos = Coord {
-            y: new_size.y - 2,
-            x: (new_size.x / 2) - (pos.size.x / 2)
-        }
-    }
+    manager.run(&mut main_page).unwrap();
 }
 
 struct Menu {
@@ -48,16 +39,13 @@ impl Menu {
             let mut temp = text.borrow_mut();
             use text::Entry;
 
-            temp.entries.push_back(Entry::new_color("Slot Machine.", ColorSet { fg: Rgb { r: 255, g: 255, b: 0 }, bg: Rgb { r: 0, g: 0, b: 0 } }));
+            temp.entries.push_back(Entry::new_color("Slot Machine.", ColorSet::new(Rgb { r: 255, g: 255, b: 0 }, Rgb { r: 0, g: 0, b: 0 }).with(Attribute::Bold)));
             temp.entries.push_back(Entry::new("Exit."));
         }
 
-        let pos = position::craft().size(Coord{x: 30, y: 2}).update(MenuUpdate {}).done();
+        let pos = position::craft().size(Coord{x: 30, y: 2}).update(Anchor::new(Point::Bottom)).done();
 
-        manager.layout.borrow_mut().objects.push(Object {
-            frame: text.clone(),
-            pos:   pos.clone(),
-        });
+        manager.layout.borrow_mut().add(Object::new(text.clone(), pos.clone()));
 
         Menu {
             text,
@@ -73,13 +61,13 @@ impl Menu {
             let mut temp = self.text.borrow_mut();
 
             temp.entries[0].colors = None;
-            temp.entries[1].colors = Some( ColorSet { fg: Rgb { r: 255, g: 255, b: 0 }, bg: Rgb { r: 0, g: 0, b: 0 } } );
+            temp.entries[1].colors = Some( ColorSet::new(Rgb { r: 255, g: 255, b: 0 }, Rgb { r: 0, g: 0, b: 0 }).with(Attribute::Bold) );
         }
         else {
             let mut temp = self.text.borrow_mut();
 
             temp.entries[1].colors = None;
-            temp.entries[0].colors = Some( ColorSet { fg: Rgb { r: 255, g: 255, b: 0 }, bg: Rgb { r: 0, g: 0, b: 0 } } );
+            temp.entries[0].colors = Some( ColorSet::new(Rgb { r: 255, g: 255, b: 0 }, Rgb { r: 0, g: 0, b: 0 }).with(Attribute::Bold) );
         }
     }
 
@@ -96,31 +84,18 @@ impl Menu {
     }
 }
 
-struct PlanetUpdate {}
-
-impl position::SizeUpdate for PlanetUpdate {
-    fn size_update(&mut self, data: &mut position::PosData, new_size: Coord) {
-        let temp = Coord{x: (new_size.x / 6), y: ((new_size.y / 5) * 3)};
-        data.pos = temp - (data.size / Coord{ x: 2, y: 2 });
-    }
-}
-
-struct MoonUpdate {
-    planet: position::Position,
-}
-
-impl position::SizeUpdate for MoonUpdate {
-    fn size_update(&mut self, data: &mut position::PosData, _new_size: Coord) {
-        let temp = self.planet.borrow().data.pos + Coord{x: 25, y: -5};
-        data.pos = temp - (data.size / Coord{ x: 2, y: 2 });
-    }
+enum Page {
+    Main,
+    Slots,
+    Spinning{ turns: [usize; 3], step: usize },
 }
 
 struct MainPage {
     planet:     Position,
-    moon:       Position,
     menu:       Menu,
     slots:      SlotMachine,
+    page:       Page,
+    quit:       bool,
 }
 
 impl MainPage {
@@ -194,103 +169,81 @@ impl MainPage {
             )
         };
 
-        let planet = position::craft().size(Coord{x: 21, y: 21}).frame_size(Coord{x: 42, y: 21}).update(PlanetUpdate{}).done();
-        let moon = position::craft().size(Coord{x: 10, y: 10}).update(MoonUpdate{planet: planet.clone()}).done();
+        let planet = position::craft().size(Coord{x: 21, y: 21}).frame_size(Coord{x: 42, y: 21}).update(Percent::pos(17, 60).at(Point::Center)).done();
+        //a child of the planet so it is placed relative to it and hidden along with it.
+        let moon = position::craft().size(Coord{x: 10, y: 10}).pos(Coord{x: 20, y: -10}).done();
     
         {
             let mut layout = manager.layout.borrow_mut();
     
-            layout.objects.push(Object{
-                frame: background_frame,
-                pos: position::craft().update(MatchSize{}).done(),
-            });
-            layout.objects.push(Object{
-                frame: planet_frame,
-                pos: planet.clone()
-            });
-            layout.objects.push(Object{
-                frame: moon_frame,
-                pos: moon.clone()
-            });
+            layout.add(Object::new(background_frame, position::craft().update(MatchSize{}).done()));
+            let planet_id = layout.add(Object::new(planet_frame, planet.clone()));
+            layout.add_child(planet_id, Object::new(moon_frame, moon));
         }
 
         MainPage {
             planet,
-            moon,
             menu: Menu::new(manager),
-            slots: SlotMachine::new(manager)
+            slots: SlotMachine::new(manager),
+            page: Page::Main,
+            quit: false,
         }
     }
 
-    pub fn main(&mut self, manager: &mut LayoutManager) {
-        let mut inputs = Vec::new();
+    fn show_slots(&mut self, show: bool) {
+        self.planet.borrow_mut().data.enabled = !show;
+        self.slots.set_enabled(show);
 
-        loop {
-            manager.draw().unwrap();
-    
-            self.planet.borrow_mut().inc_offset(Coord{ x: 1, y: 0 });
-    
-            manager.fps_input(&mut inputs);
-
-            for input in &inputs {
-                if let Input::KeyBoard(e) = input {
-                    use crossterm::event::KeyCode::*;
-
-                    match e.code {
-                        Esc => {
-                            return
-                        }
-                        Up | Down => {
-                            self.menu.toggle()
-                        }
-                        Enter => {
-                            if self.menu.exit() {
-                                return
-                            }
-                            else {
-                                self.leave(manager);
-                                break
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
+        if show {
+            self.menu.disable();
+            self.page = Page::Slots;
+        }
+        else {
+            self.menu.enabled();
+            self.page = Page::Main;
         }
     }
+}
 
-    fn leave(&mut self, manager: &mut LayoutManager) {
-        self.moon.borrow_mut().data.enabled = false;
-        self.planet.borrow_mut().data.enabled = false;
-        self.menu.disable();
+impl App<LayoutManager> for MainPage {
+    fn handle_input(&mut self, _manager: &mut LayoutManager, input: Input) {
+        use crossterm::event::KeyCode::*;
 
-        self.slots.main(manager);
+        let e = match input {
+            Input::KeyBoard(e) => e,
+            _ => return,
+        };
 
-        self.moon.borrow_mut().data.enabled = true;
-        self.planet.borrow_mut().data.enabled = true;
-        self.menu.enabled();
+        match (&self.page, e.code) {
+            (Page::Main, Esc)         => self.quit = true,
+            (Page::Main, Up | Down)   => self.menu.toggle(),
+            (Page::Main, Enter)       => {
+                if self.menu.exit() { self.quit = true }
+                               else { self.show_slots(true) }
+            }
+            (Page::Slots, Esc)        => self.show_slots(false),
+            (Page::Slots, Enter)      => self.page = Page::Spinning{ turns: SlotMachine::roll(), step: 0 },
+            _ => {}
+        }
     }
-}
 
-struct MachineUpdate {}
-
-impl position::SizeUpdate for MachineUpdate {
-    fn size_update(&mut self, pos: &mut position::PosData, new_size: Coord) {
-        pos.pos = (new_size / Coord{x: 2, y: 2}) - (pos.size / Coord{x: 2, y: 2})
+    fn tick(&mut self, _manager: &mut LayoutManager, _dt: Duration) {
+        match &mut self.page {
+            Page::Main => self.planet.borrow_mut().inc_offset(Coord{ x: 1, y: 0 }),
+            Page::Slots => {}
+            Page::Spinning{ turns, step } => {
+                if self.slots.spin_step(turns, *step) {
+                    *step += 1;
+                }
+                else {
+                    self.page = Page::Slots;
+                }
+            }
+        }
     }
-}
 
-struct SlotUpdate {
-    machine: Position,
-    num:     i32,
-}
-
-impl position::SizeUpdate for SlotUpdate {
-    fn size_update(&mut self, pos: &mut position::PosData, _new_size: Coord) {
-        let mut temp = self.machine.borrow().data.pos + Coord{x: 2, y: 2};
-        temp.x += 17 * self.num;
-
-        pos.pos = temp
+    fn should_quit(&self) -> bool {
+        self.quit
     }
 }
 
@@ -425,50 +378,36 @@ impl SlotMachine {
             basic::new(Coord {x: 16, y: 80}, sprite).unwrap()
         };
 
-        let machine = position::craft().size(Coord {x: 54, y: 20}).enabled(false).update(MachineUpdate{}).done();
+        let machine = position::craft().size(Coord {x: 54, y: 20}).enabled(false).update(Anchor::new(Point::Center)).done();
         let slot1 = position::craft().size(Coord {x: 16, y: 16}).frame_size(Coord {x: 16, y: 80}).enabled(false).update(
-            SlotUpdate{
-                machine: machine.clone(),
-                num: 0,
+            RelativeTo {
+                target:       machine.clone(),
+                target_point: Point::TopLeft,
+                point:        Point::TopLeft,
+                offset:       Coord{x: 2, y: 2},
             }).done();
         let slot2 = position::craft().size(Coord {x: 16, y: 16}).frame_size(Coord {x: 16, y: 80}).enabled(false).update(
-            SlotUpdate{
-                machine: machine.clone(),
-                num: 1,
+            RelativeTo {
+                target:       machine.clone(),
+                target_point: Point::TopLeft,
+                point:        Point::TopLeft,
+                offset:       Coord{x: 19, y: 2},
             }).done();
         let slot3 = position::craft().size(Coord {x: 16, y: 16}).frame_size(Coord {x: 16, y: 80}).enabled(false).update(
-            SlotUpdate{
-                machine: machine.clone(),
-                num: 2,
+            RelativeTo {
+                target:       machine.clone(),
+                target_point: Point::TopLeft,
+                point:        Point::TopLeft,
+                offset:       Coord{x: 36, y: 2},
             }).done();
 
         {
             let mut layout = manager.layout.borrow_mut();
 
-            layout.objects.push(
-                Object {
-                    pos: machine.clone(),
-                    frame: machine_frame,
-                }
-            );
-            layout.objects.push(
-                Object {
-                    pos: slot1.clone(),
-                    frame: slot_frame.clone(),
-                }
-            );
-            layout.objects.push(
-                Object {
-                    pos: slot2.clone(),
-                    frame: slot_frame.clone(),
-                }
-            );
-            layout.objects.push(
-                Object {
-                    pos: slot3.clone(),
-                    frame: slot_frame,
-                }
-            );
+            layout.add(Object::new(machine_frame, machine.clone()));
+            layout.add(Object::new(slot_frame.clone(), slot1.clone()));
+            layout.add(Object::new(slot_frame.clone(), slot2.clone()));
+            layout.add(Object::new(slot_frame, slot3.clone()));
         }
 
         SlotMachine {
@@ -479,69 +418,37 @@ impl SlotMachine {
         }
     }
 
-    pub fn main(&mut self, manager: &mut LayoutManager) {
-        self.machine.borrow_mut().data.enabled = true;
-        self.slot1.borrow_mut().data.enabled = true;
-        self.slot2.borrow_mut().data.enabled = true;
-        self.slot3.borrow_mut().data.enabled = true;
-
-        let mut inputs = Vec::new();
-
-        'out: loop {
-            manager.draw().unwrap();
-
-            manager.fps_input(&mut inputs);
-
-            for input in &inputs {
-                if let Input::KeyBoard(e) = input {
-                    use crossterm::event::KeyCode::*;
-
-                    match e.code {
-                        Esc => {
-                            break 'out
-                        }
-                        Enter => {
-                            self.spin(manager);
-                            break
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        self.machine.borrow_mut().data.enabled = false;
-        self.slot1.borrow_mut().data.enabled = false;
-        self.slot2.borrow_mut().data.enabled = false;
-        self.slot3.borrow_mut().data.enabled = false;
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.machine.borrow_mut().data.enabled = enabled;
+        self.slot1.borrow_mut().data.enabled = enabled;
+        self.slot2.borrow_mut().data.enabled = enabled;
+        self.slot3.borrow_mut().data.enabled = enabled;
     }
 
-    fn spin(&mut self, manager: &mut LayoutManager) {
+    ///How many turns each slot spins for, smallest first.
+    pub fn roll() -> [usize; 3] {
         // get some kind of random numbers.
         let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() % 1000;
-        let mut digits = vec! [(time / 100) as usize + 1];
-        let time = time  % 100;
-        digits.push((time / 10) as usize + 1);
-        digits.push((time % 10) as usize + 1);
+        let mut digits = [(time / 100) as usize + 1, ((time % 100) / 10) as usize + 1, (time % 10) as usize + 1];
 
         digits.sort();
-        let mut inputs = Vec::new();
+        digits
+    }
 
-        for i in 0..digits[2] {
-            for _ in 0..16 {
-                self.slot3.borrow_mut().inc_offset(Coord {x: 0, y: 1});
+    ///Moves the slots one step, a turn is 16 steps. Returns false once every slot has stopped.
+    pub fn spin_step(&mut self, turns: &[usize; 3], step: usize) -> bool {
+        let i = step / 16;
+        if i >= turns[2] { return false }
 
-                if i < digits[1] {
-                    self.slot2.borrow_mut().inc_offset(Coord {x: 0, y: 1});
-                }
-                if i < digits[0] {
-                    self.slot1.borrow_mut().inc_offset(Coord {x: 0, y: 1});
-                }
+        self.slot3.borrow_mut().inc_offset(Coord {x: 0, y: 1});
 
-                manager.draw().unwrap();
-                manager.fps_input(&mut inputs);
-            }
+        if i < turns[1] {
+            self.slot2.borrow_mut().inc_offset(Coord {x: 0, y: 1});
+        }
+        if i < turns[0] {
+            self.slot1.borrow_mut().inc_offset(Coord {x: 0, y: 1});
         }
 
+        true
     }
 }
\ No newline at end of file
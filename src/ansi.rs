@@ -0,0 +1,479 @@
+//! Turns text with ANSI escape codes, like the colored output of other programs, into ColorStrings, text entries or pixels.
+//!
+//! Colors (16, 256 and rgb) and attributes set with SGR codes are kept, every other escape sequence is stripped.
+//! A carriage return that isn't followed by a new line starts the line over, the way progress bars expect.
+use crate::prelude::*;
+use crate::{ColorString, ColorSlice};
+use crate::frame_types::text::Entry;
+use crate::markup::Style;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+///Longest escape sequence kept before it is given up on.
+const MAX_SEQUENCE: usize = 64;
+
+///Foreground colors of codes 30 to 37, and 90 to 97 for the bright ones. Backgrounds are the same 10 higher.
+const COLORS: [Color; 8] = [
+    Color::Black, Color::DarkRed, Color::DarkGreen, Color::DarkYellow,
+    Color::DarkBlue, Color::DarkMagenta, Color::DarkCyan, Color::Grey,
+];
+
+const BRIGHT_COLORS: [Color; 8] = [
+    Color::DarkGrey, Color::Red, Color::Green, Color::Yellow,
+    Color::Blue, Color::Magenta, Color::Cyan, Color::White,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Text,
+    ///After an escape.
+    Escape,
+    ///In a control sequence, `ESC [`.
+    Csi,
+    ///In a string like an operating system command, `ESC ]`, which ends with a bell or `ESC \`.
+    Str,
+    ///After an escape in a string.
+    StrEscape,
+    ///In some other escape sequence, waiting for its last byte.
+    Other,
+}
+
+pub fn new() -> Parser {
+    Parser::new()
+}
+
+/// Parses output a chunk at a time, so escape sequences and characters can be split between chunks.
+/// Colors carry over from one line to the next like they do in a terminal.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - push
+/// - finish
+pub struct Parser {
+    ///Colors of text that has some colors or attributes set but not others, white on black by default.
+    pub default: ColorSet,
+    state:       State,
+    sequence:    Vec<u8>,
+    ///bytes of a character that isn't finished yet.
+    utf8:        Vec<u8>,
+    style:       Style,
+    line:        ColorString,
+    line_len:    usize,
+    carriage_return: bool,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Parser {
+            default:  ColorSet::new(Color::Rgb{r: 255, g: 255, b: 255}, Color::Rgb{r: 0, g: 0, b: 0}),
+            state:    State::Text,
+            sequence: Vec::new(),
+            utf8:     Vec::new(),
+            style:    Style::default(),
+            line:     ColorString::from(""),
+            line_len: 0,
+            carriage_return: false,
+        }
+    }
+
+    ///Parses the next chunk, returning the lines it finished without their new lines.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<ColorString> {
+        let mut lines = Vec::new();
+
+        for &byte in bytes {
+            match self.state {
+                State::Text => {
+                    if byte == 0x1b {
+                        self.broken_char(&mut lines);
+                        self.state = State::Escape;
+                    }
+                    else {
+                        self.text_byte(byte, &mut lines);
+                    }
+                }
+                State::Escape => {
+                    self.sequence.clear();
+
+                    self.state = match byte {
+                        b'['                     => State::Csi,
+                        b']' | b'P' | b'X' | b'^' | b'_' => State::Str,
+                        0x20..=0x2f              => State::Other,
+                        _                        => State::Text,
+                    };
+                }
+                State::Csi => {
+                    if (0x40..=0x7e).contains(&byte) {
+                        if byte == b'm' {
+                            self.sgr();
+                        }
+                        self.state = State::Text;
+                    }
+                    //a sequence that is too long is read to the end but not used.
+                    else if self.sequence.len() < MAX_SEQUENCE {
+                        self.sequence.push(byte);
+                    }
+                }
+                State::Str => {
+                    match byte {
+                        0x07 => self.state = State::Text,
+                        0x1b => self.state = State::StrEscape,
+                        _    => {}
+                    }
+                }
+                State::StrEscape => {
+                    self.state = if byte == b'\\' { State::Text }
+                                             else { State::Str };
+                }
+                State::Other => {
+                    if !(0x20..=0x2f).contains(&byte) {
+                        self.state = State::Text;
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+
+    ///Returns the last line if it has anything on it, an unfinished escape sequence or character is dropped.
+    pub fn finish(&mut self) -> Option<ColorString> {
+        let mut lines = Vec::new();
+        self.broken_char(&mut lines);
+
+        self.state = State::Text;
+        self.carriage_return = false;
+
+        let line = self.take_line();
+        if line.string.is_empty() { None }
+                             else { Some(line) }
+    }
+
+    fn text_byte(&mut self, byte: u8, lines: &mut Vec<ColorString>) {
+        self.utf8.push(byte);
+
+        match std::str::from_utf8(&self.utf8) {
+            Ok(s) => {
+                let c = s.chars().next().unwrap();
+                self.utf8.clear();
+                self.char(c, lines);
+            }
+            //waiting for the rest of the character.
+            Err(e) if e.error_len().is_none() => {}
+            Err(_) => {
+                let retry = self.utf8.len() > 1;
+                self.utf8.clear();
+                self.char(char::REPLACEMENT_CHARACTER, lines);
+
+                //the byte might start a character of its own.
+                if retry {
+                    self.text_byte(byte, lines);
+                }
+            }
+        }
+    }
+
+    ///A character that was cut off by an escape or the end.
+    fn broken_char(&mut self, lines: &mut Vec<ColorString>) {
+        if !self.utf8.is_empty() {
+            self.utf8.clear();
+            self.char(char::REPLACEMENT_CHARACTER, lines);
+        }
+    }
+
+    fn char(&mut self, c: char, lines: &mut Vec<ColorString>) {
+        if self.carriage_return {
+            self.carriage_return = false;
+
+            if c != '\n' {
+                self.take_line();
+            }
+        }
+
+        match c {
+            '\n' => lines.push(self.take_line()),
+            '\r' => self.carriage_return = true,
+            '\t' => self.push_char(c),
+            c if c.is_control() => {}
+            c => self.push_char(c),
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.line.string.push(c);
+
+        if self.style != Style::default() {
+            let colors = self.style.apply(self.default);
+
+            match self.line.colors.last_mut() {
+                Some(slice) if slice.end == self.line_len && slice.colors == colors => slice.end += 1,
+                _ => self.line.colors.push(ColorSlice { start: self.line_len, end: self.line_len + 1, colors }),
+            }
+        }
+
+        self.line_len += 1;
+    }
+
+    fn take_line(&mut self) -> ColorString {
+        self.line_len = 0;
+        std::mem::replace(&mut self.line, ColorString::from(""))
+    }
+
+    ///Applies the select graphic rendition codes in the sequence.
+    fn sgr(&mut self) {
+        //anything other than numbers is a private sequence that isn't supported.
+        if self.sequence.len() == MAX_SEQUENCE || !self.sequence.iter().all(|b| b.is_ascii_digit() || *b == b';') { return }
+
+        let codes: Vec<u32> = std::str::from_utf8(&self.sequence).unwrap()
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+
+        let mut codes = codes.into_iter();
+
+        while let Some(code) = codes.next() {
+            let style = &mut self.style;
+
+            match code {
+                0  => *style = Style::default(),
+                1  => style.attributes.set(Attribute::Bold),
+                2  => style.attributes.set(Attribute::Dim),
+                3  => style.attributes.set(Attribute::Italic),
+                4  => style.attributes.set(Attribute::Underlined),
+                5  => style.attributes.set(Attribute::SlowBlink),
+                6  => style.attributes.set(Attribute::RapidBlink),
+                7  => style.attributes.set(Attribute::Reverse),
+                8  => style.attributes.set(Attribute::Hidden),
+                9  => style.attributes.set(Attribute::CrossedOut),
+                21 => style.attributes.set(Attribute::DoubleUnderlined),
+                22 => { style.attributes.unset(Attribute::Bold); style.attributes.unset(Attribute::Dim) }
+                23 => style.attributes.unset(Attribute::Italic),
+                24 => { style.attributes.unset(Attribute::Underlined); style.attributes.unset(Attribute::DoubleUnderlined) }
+                25 => { style.attributes.unset(Attribute::SlowBlink); style.attributes.unset(Attribute::RapidBlink) }
+                27 => style.attributes.unset(Attribute::Reverse),
+                28 => style.attributes.unset(Attribute::Hidden),
+                29 => style.attributes.unset(Attribute::CrossedOut),
+                30..=37   => style.fg = Some(COLORS[code as usize - 30]),
+                40..=47   => style.bg = Some(COLORS[code as usize - 40]),
+                90..=97   => style.fg = Some(BRIGHT_COLORS[code as usize - 90]),
+                100..=107 => style.bg = Some(BRIGHT_COLORS[code as usize - 100]),
+                39 => style.fg = None,
+                49 => style.bg = None,
+                38 => style.fg = extended_color(&mut codes).or(style.fg),
+                48 => style.bg = extended_color(&mut codes).or(style.bg),
+                _  => {}
+            }
+        }
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Reads the rest of a 256 color `5;n` or rgb `2;r;g;b` code.
+fn extended_color(codes: &mut impl Iterator<Item = u32>) -> Option<Color> {
+    let mut next = || codes.next().map(|code| code.min(255) as u8);
+
+    match next()? {
+        5 => Some(Color::AnsiValue(next()?)),
+        2 => Some(Color::Rgb{r: next()?, g: next()?, b: next()?}),
+        _ => None,
+    }
+}
+
+///Parses all of the text into lines.
+pub fn lines(bytes: &[u8]) -> Vec<ColorString> {
+    parse_all(Parser::new(), bytes)
+}
+
+fn parse_all(mut parser: Parser, bytes: &[u8]) -> Vec<ColorString> {
+    let mut lines = parser.push(bytes);
+    lines.extend(parser.finish());
+    lines
+}
+
+///Parses all of the text into one ColorString, with new lines between the lines.
+pub fn parse(bytes: &[u8]) -> ColorString {
+    let mut out = ColorString::from("");
+    let mut len = 0;
+
+    for (i, line) in lines(bytes).into_iter().enumerate() {
+        if i != 0 {
+            out.string.push('\n');
+            len += 1;
+        }
+
+        out.colors.extend(line.colors.iter().map(|slice| ColorSlice { start: slice.start + len, end: slice.end + len, ..*slice }));
+        len += line.string.chars().count();
+        out.string += &line.string;
+    }
+
+    out
+}
+
+///Parses all of the text into an entry for each line.
+pub fn entries(bytes: &[u8]) -> Vec<Entry> {
+    lines(bytes).into_iter().map(Entry::new).collect()
+}
+
+///Parses all of the text into pixels for a Basic frame, as wide as the longest line and as tall as the number of lines.
+///The rest of each line is filled with default, which also gives the colors of text that has none.
+///Tabs go to the next multiple of 8 columns.
+pub fn pixels(bytes: &[u8], default: PixelData) -> (Coord, Vec<Pixel>) {
+    let mut parser = Parser::new();
+    parser.default = default.get_color_set();
+
+    let rows: Vec<Vec<Pixel>> = parse_all(parser, bytes).iter().map(|line| {
+        let mut row = Vec::new();
+
+        //char index of the start of the cluster, which the colors are looked up by.
+        let mut i = 0;
+
+        for cluster in line.string.graphemes(true) {
+            let colors = line.get_color(i).unwrap_or(default.get_color_set());
+            i += cluster.chars().count();
+
+            match PixelData::cluster(cluster, colors) {
+                Some(data) if data.character == '\t' => {
+                    let spaces = 8 - row.len() % 8;
+                    row.resize(row.len() + spaces, Pixel::Opaque(data.with_char(' ')));
+                }
+                //other control characters and marks with nothing to go on are dropped.
+                Some(data) if data.character.is_control() => {}
+                Some(data) => {
+                    row.push(Pixel::Opaque(data));
+
+                    if data.width() == 2 {
+                        row.push(Pixel::Continuation);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        row
+    }).collect();
+
+    let size = Coord {
+        x: rows.iter().map(|row| row.len()).max().unwrap_or(0) as i32,
+        y: rows.len() as i32,
+    };
+
+    let pixels = rows.into_iter().flat_map(|mut row| {
+        row.resize(size.x as usize, Pixel::Opaque(default));
+        row
+    }).collect();
+
+    (size, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::basic;
+    use crate::manager::Manager;
+    use crate::test_helpers::*;
+
+    fn slice(start: usize, end: usize, colors: ColorSet) -> ColorSlice {
+        ColorSlice { start, end, colors }
+    }
+
+    #[test]
+    fn colors() {
+        let black = Color::Rgb{r: 0, g: 0, b: 0};
+
+        let parsed = parse(b"a\x1b[1;31mb\x1b[22;38;5;208mc\x1b[48;2;1;2;3md\x1b[0me\x1b[94mf\x1b[39mg");
+
+        assert_eq!(parsed.string, "abcdefg");
+        assert_eq!(parsed.colors, vec![
+            slice(1, 2, ColorSet::new(Color::DarkRed, black).with(Attribute::Bold)),
+            slice(2, 3, ColorSet::new(Color::AnsiValue(208), black)),
+            slice(3, 4, ColorSet::new(Color::AnsiValue(208), Color::Rgb{r: 1, g: 2, b: 3})),
+            slice(5, 6, ColorSet::new(Color::Blue, black)),
+        ]);
+
+        //colors carry over new lines, and other sequences are stripped.
+        let parsed = parse(b"\x1b[32mok\n\x1b]8;;http://x\x07go\x1b]8;;\x1b\\\x1b[2K\x1b[?25l\x1b(B!\x1b[m");
+        let green = ColorSet::new(Color::DarkGreen, black);
+
+        assert_eq!(parsed.string, "ok\ngo!");
+        assert_eq!(parsed.colors, vec![slice(0, 2, green), slice(3, 6, green)]);
+    }
+
+    #[test]
+    fn chunks() {
+        let text = "\x1b[31m世界\x1b[0m\r\nprogress 10%\rprogress 99%\n\x07done\x1b[";
+        let bytes = text.as_bytes();
+
+        //every split of the input gives the same lines.
+        for split in 0..bytes.len() {
+            let mut parser = new();
+
+            let mut lines = parser.push(&bytes[..split]);
+            lines.extend(parser.push(&bytes[split..]));
+            lines.extend(parser.finish());
+
+            let strings: Vec<&str> = lines.iter().map(|line| line.string.as_str()).collect();
+            assert_eq!(strings, vec!["世界", "progress 99%", "done"], "split at {}", split);
+            assert_eq!(lines[0].colors.len(), 1);
+        }
+
+        //broken characters are replaced.
+        let mut parser = new();
+        assert!(parser.push(b"a\xe4\xb8").is_empty());
+        assert_eq!(parser.push(b"b\xff\n")[0].string, "a\u{fffd}b\u{fffd}");
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[test]
+    fn to_pixels() {
+        let default = PixelData::new('.', Color::White, Color::Black);
+        let (size, pixels) = pixels(b"\x1b[7mab\x1b[0m\tc\n\xe4\xb8\x96", default);
+
+        assert_eq!(size, Coord{x: 9, y: 2});
+        assert_eq!(pixels[0], Pixel::Opaque(PixelData::new_color_set('a', ColorSet::new(Color::White, Color::Black).with(Attribute::Reverse))));
+
+        let basic = basic::new(size, pixels).unwrap();
+        let mut buf = ScreenBuf::new(size);
+        basic.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+
+        assert_chars(&buf.buffer, "
+ab      c
+世.......");
+    }
+
+    #[test]
+    fn pixel_clusters() {
+        let default = PixelData::new(' ', Color::White, Color::Black);
+        let (size, pixels) = pixels("\x1b[1mx\u{301}\x1b[0m\u{e01}\u{e35}\u{301}".as_bytes(), default);
+
+        //the mark at the start of the line has nothing to go on.
+        assert_eq!(size, Coord{x: 2, y: 1});
+        assert_eq!(pixels[0], Pixel::Opaque(PixelData {
+            marks: Marks::new("\u{301}"),
+            ..PixelData::new_color_set('x', ColorSet::new(Color::White, Color::Black).with(Attribute::Bold))
+        }));
+        assert_eq!(pixels[1], Pixel::Opaque(PixelData { marks: Marks::new("\u{e35}\u{301}"), ..default.with_char('\u{e01}') }));
+    }
+
+    #[test]
+    fn printed_attributes() {
+        let default = PixelData::new(' ', Color::White, Color::Black);
+        let (size, pixels) = pixels(b"\x1b[21ma\x1b[1mb\x1b[24mc", default);
+
+        let mut manager = Manager::with_backend(TestBackend::new(size)).unwrap();
+        manager.draw(basic::new(size, pixels).unwrap()).unwrap();
+
+        let attributes: Vec<Attributes> = (0..3).map(|x| match manager.backend().buffer().get(Coord{x, y: 0}) {
+            Pixel::Opaque(data) => data.attributes,
+            other => panic!("expected a printed cell, found {:?}", other),
+        }).collect();
+
+        let double = Attributes::from(Attribute::DoubleUnderlined);
+
+        assert_eq!(attributes, vec![double, double | Attribute::Bold, Attribute::Bold.into()]);
+    }
+}
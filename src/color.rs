@@ -0,0 +1,243 @@
+use crate::prelude::*;
+
+/// RGB values for the 16 named colors, in the same order as ansi values 0-15.
+/// Terminals are free to pick their own so these are the common xterm defaults.
+const NAMED: [(u8, u8, u8); 16] = [
+    (  0,   0,   0), //Black
+    (128,   0,   0), //DarkRed
+    (  0, 128,   0), //DarkGreen
+    (128, 128,   0), //DarkYellow
+    (  0,   0, 128), //DarkBlue
+    (128,   0, 128), //DarkMagenta
+    (  0, 128, 128), //DarkCyan
+    (192, 192, 192), //Grey
+    (128, 128, 128), //DarkGrey
+    (255,   0,   0), //Red
+    (  0, 255,   0), //Green
+    (255, 255,   0), //Yellow
+    (  0,   0, 255), //Blue
+    (255,   0, 255), //Magenta
+    (  0, 255, 255), //Cyan
+    (255, 255, 255), //White
+];
+
+///The named colors in the same order as NAMED.
+const NAMED_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+    Color::Grey,
+    Color::DarkGrey,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+///Levels used by the 6x6x6 color cube in ansi values 16-231.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can show.
+/// ## Functions
+/// - detect
+/// - from_env
+///
+/// ## Methods
+/// - convert
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    ///Any RGB color.
+    TrueColor,
+    ///The 256 ansi values.
+    Ansi256,
+    ///Only the 16 named colors.
+    Ansi16,
+}
+
+impl ColorDepth {
+    ///Guesses the color depth of the current terminal from the COLORTERM and TERM environment variables.
+    pub fn detect() -> ColorDepth {
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+
+        ColorDepth::from_env(colorterm.as_deref(), term.as_deref())
+    }
+
+    ///Guesses the color depth from the given values of COLORTERM and TERM.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+        if let Some("truecolor" | "24bit") = colorterm {
+            return ColorDepth::TrueColor
+        }
+
+        match term {
+            Some(term) if term.ends_with("-direct") => ColorDepth::TrueColor,
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            Some(_) => ColorDepth::Ansi16,
+            //windows terminals don't set TERM but can all show RGB.
+            None if cfg!(windows) => ColorDepth::TrueColor,
+            None => ColorDepth::Ansi16,
+        }
+    }
+
+    ///Returns the closest color the terminal can show.
+    pub fn convert(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::TrueColor, _) => color,
+            (ColorDepth::Ansi256, Color::Rgb{r, g, b}) => Color::AnsiValue(nearest_ansi256((r, g, b))),
+            (ColorDepth::Ansi16, Color::Rgb{..} | Color::AnsiValue(_)) => {
+                match to_rgb(color) {
+                    Some(rgb) => nearest_named(rgb),
+                    None => color,
+                }
+            }
+            _ => color,
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| -> u32 { (a as i32 - b as i32).pow(2) as u32 };
+
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+///Index of the cube level closest to the value.
+fn nearest_level(value: u8) -> usize {
+    (0..CUBE.len()).min_by_key(|i| (CUBE[*i] as i32 - value as i32).abs()).unwrap()
+}
+
+///Picks between the closest color in the color cube and the closest grey.
+fn nearest_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (nearest_level(rgb.0), nearest_level(rgb.1), nearest_level(rgb.2));
+    let cube = (16 + r * 36 + g * 6 + b) as u8;
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = (232 + (average.saturating_sub(3) / 10).min(23)) as u8;
+
+    if distance(rgb, ansi_to_rgb(grey)) < distance(rgb, ansi_to_rgb(cube)) { grey }
+                                                                        else { cube }
+}
+
+fn nearest_named(rgb: (u8, u8, u8)) -> Color {
+    let i = (0..NAMED.len()).min_by_key(|i| distance(rgb, NAMED[*i])).unwrap();
+
+    NAMED_COLORS[i]
+}
+
+///Returns the RGB value of the color, None for Reset because it is up to the terminal.
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let rgb = match color {
+        Color::Reset       => return None,
+        Color::Black       => NAMED[0],
+        Color::DarkRed     => NAMED[1],
+        Color::DarkGreen   => NAMED[2],
+        Color::DarkYellow  => NAMED[3],
+        Color::DarkBlue    => NAMED[4],
+        Color::DarkMagenta => NAMED[5],
+        Color::DarkCyan    => NAMED[6],
+        Color::Grey        => NAMED[7],
+        Color::DarkGrey    => NAMED[8],
+        Color::Red         => NAMED[9],
+        Color::Green       => NAMED[10],
+        Color::Yellow      => NAMED[11],
+        Color::Blue        => NAMED[12],
+        Color::Magenta     => NAMED[13],
+        Color::Cyan        => NAMED[14],
+        Color::White       => NAMED[15],
+        Color::Rgb{r, g, b} => (r, g, b),
+        Color::AnsiValue(x) => ansi_to_rgb(x),
+    };
+
+    Some(rgb)
+}
+
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => NAMED[value as usize],
+        16..=231 => {
+            let i = value - 16;
+            (CUBE[(i / 36) as usize], CUBE[((i / 6) % 6) as usize], CUBE[(i % 6) as usize])
+        }
+        _ => {
+            let grey = 8 + (value - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+///Mixes over on top of under, an alpha of 255 is only over and 0 is only under.
+///Colors without an RGB value can't be mixed so whichever one has more weight is used.
+pub fn blend(over: Color, under: Color, alpha: u8) -> Color {
+    match (to_rgb(over), to_rgb(under)) {
+        (Some(o), Some(u)) => {
+            let mix = |o: u8, u: u8| -> u8 {
+                ((o as u32 * alpha as u32 + u as u32 * (255 - alpha as u32) + 127) / 255) as u8
+            };
+
+            Color::Rgb{r: mix(o.0, u.0), g: mix(o.1, u.1), b: mix(o.2, u.2)}
+        }
+        _ => if alpha >= 128 { over } else { under },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb() {
+        let over  = Color::Rgb{r: 255, g: 0, b: 100};
+        let under = Color::Rgb{r: 0, g: 0, b: 200};
+
+        assert_eq!(blend(over, under, 255), over);
+        assert_eq!(blend(over, under, 0), under);
+        assert_eq!(blend(over, under, 128), Color::Rgb{r: 128, g: 0, b: 150});
+    }
+
+    #[test]
+    fn named() {
+        assert_eq!(to_rgb(Color::DarkCyan), Some((0, 128, 128)));
+        assert_eq!(to_rgb(Color::AnsiValue(9)), to_rgb(Color::Red));
+        assert_eq!(to_rgb(Color::AnsiValue(196)), Some((255, 0, 0)));
+        assert_eq!(to_rgb(Color::AnsiValue(244)), Some((128, 128, 128)));
+
+        assert_eq!(blend(Color::Black, Color::White, 64), Color::Rgb{r: 191, g: 191, b: 191});
+
+        //reset has no value so it can't be mixed.
+        assert_eq!(blend(Color::Reset, Color::White, 200), Color::Reset);
+        assert_eq!(blend(Color::Reset, Color::White, 100), Color::White);
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-direct")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(Some(""), Some("screen")), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn convert() {
+        let orange = Color::Rgb{r: 255, g: 135, b: 0};
+
+        assert_eq!(ColorDepth::TrueColor.convert(orange), orange);
+        assert_eq!(ColorDepth::Ansi256.convert(orange), Color::AnsiValue(208));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Rgb{r: 0, g: 0, b: 0}), Color::AnsiValue(16));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Rgb{r: 100, g: 100, b: 100}), Color::AnsiValue(241));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Red), Color::Red);
+
+        assert_eq!(ColorDepth::Ansi16.convert(orange), Color::Yellow);
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Rgb{r: 10, g: 0, b: 120}), Color::DarkBlue);
+        assert_eq!(ColorDepth::Ansi16.convert(Color::AnsiValue(196)), Color::Red);
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Reset), Color::Reset);
+    }
+}
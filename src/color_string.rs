@@ -1,14 +1,15 @@
 use crate::prelude::ColorSet;
 
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ColorSlice {
     pub start:  usize,
     pub end:    usize,
     pub colors: ColorSet,
 }
 
-#[derive(Clone)]
+///The color slices are sorted and don't overlap, start and end are char indices.
+#[derive(Clone, Debug, PartialEq)]
 pub struct ColorString {
     pub string: String,
     pub colors: Vec<ColorSlice>
@@ -16,14 +17,12 @@ pub struct ColorString {
 
 impl ColorString {
     pub fn get_color(&self, pos: usize) -> Option<ColorSet> {
-        for slice in &self.colors {
-            if pos >= slice.start && pos < slice.end {
-                return Some(slice.colors)
-            }
-            if pos < slice.start { break }
-        }
+        let index = self.colors.partition_point(|slice| slice.end <= pos);
 
-        None
+        match self.colors.get(index) {
+            Some(slice) if slice.start <= pos => Some(slice.colors),
+            _ => None,
+        }
     }
 }
 
@@ -79,12 +79,12 @@ impl IBasic {
         self.pixels[index] = pixel;
     }
 
-    ///changes the current character. Only works if the pixel is Opaque.
+    ///changes the current character, dropping any marks it had. Only works if the pixel is Opaque.
     pub fn set_char(&mut self, coord: Coord, c: char) {
         let index = self.flat_pos(coord);
 
         if let Some(data) = self.pixels[index].as_mut() {
-            data.character = c;
+            *data = data.with_char(c);
         }
     }
 
@@ -0,0 +1,503 @@
+//! The editing behind TextInput and TextArea.
+use crate::prelude::*;
+
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::ops::Range;
+
+///Most edits that can be undone.
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Clone)]
+struct Snapshot {
+    text:   String,
+    cursor: usize,
+}
+
+/// Text with a cursor, a selection and undo history. Positions are byte indices that are always on grapheme boundaries.
+pub(crate) struct Editor {
+    pub text:       String,
+    pub cursor:     usize,
+    ///where the selection started, it goes from here to the cursor.
+    pub anchor:     Option<usize>,
+    pub multi_line: bool,
+    ///Word jumps go to the start or end, so a masked password doesn't give away where its words are.
+    pub one_word:   bool,
+    undo:           Vec<Snapshot>,
+    redo:           Vec<Snapshot>,
+    ///the last edit was typing, so more typing joins the same undo step.
+    typing:         bool,
+}
+
+impl Editor {
+    pub fn new(multi_line: bool) -> Self {
+        Editor {
+            text:       String::new(),
+            cursor:     0,
+            anchor:     None,
+            multi_line,
+            one_word:   false,
+            undo:       Vec::new(),
+            redo:       Vec::new(),
+            typing:     false,
+        }
+    }
+
+    ///Replaces all of the text, which can be undone.
+    pub fn set_text(&mut self, text: &str) {
+        self.save();
+        self.text = self.clean(text);
+        self.cursor = self.text.len();
+        self.anchor = None;
+    }
+
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.cursor = self.boundary(pos);
+        self.anchor = None;
+        self.typing = false;
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+
+        if anchor == self.cursor { None }
+        else { Some(anchor.min(self.cursor)..anchor.max(self.cursor)) }
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+    }
+
+    ///Replaces the selection with the text, or puts it at the cursor.
+    pub fn insert(&mut self, text: &str) {
+        let text = self.clean(text);
+        if text.is_empty() && self.selection().is_none() { return }
+
+        self.save();
+        self.delete_selection();
+
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.step(true)
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.step(false)
+    }
+
+    ///Returns false for keys it doesn't use so they can go to someone else.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        if key.kind == KeyEventKind::Release { return false }
+
+        let ctrl  = key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Char(c) if !ctrl => {
+                //typing joins the undo step of the typing before it, up to the end of a word.
+                if self.typing && self.selection().is_none() {
+                    self.text.insert(self.cursor, c);
+                    self.cursor += c.len_utf8();
+                }
+                else {
+                    self.insert(&c.to_string());
+                }
+
+                self.typing = !c.is_whitespace();
+                return true
+            }
+            KeyCode::Char('a') if ctrl => self.select_all(),
+            KeyCode::Char('z') if ctrl && shift => { self.redo(); }
+            KeyCode::Char('z') if ctrl => { self.undo(); }
+            KeyCode::Char('Z') if ctrl => { self.redo(); }
+            KeyCode::Char('y') if ctrl => { self.redo(); }
+            KeyCode::Char('w') if ctrl => self.delete_to(self.word_left(self.cursor)),
+            KeyCode::Enter if self.multi_line && !ctrl => self.insert("\n"),
+            KeyCode::Backspace if ctrl => self.delete_to(self.word_left(self.cursor)),
+            KeyCode::Backspace         => self.delete_to(self.prev_boundary(self.cursor)),
+            KeyCode::Delete if ctrl    => self.delete_to(self.word_right(self.cursor)),
+            KeyCode::Delete            => self.delete_to(self.next_boundary(self.cursor)),
+            KeyCode::Left if ctrl      => self.move_to(self.word_left(self.cursor), shift),
+            KeyCode::Left              => self.move_to(self.prev_boundary(self.cursor), shift),
+            KeyCode::Right if ctrl     => self.move_to(self.word_right(self.cursor), shift),
+            KeyCode::Right             => self.move_to(self.next_boundary(self.cursor), shift),
+            KeyCode::Home if ctrl      => self.move_to(0, shift),
+            KeyCode::Home              => self.move_to(self.line_start(self.cursor), shift),
+            KeyCode::End if ctrl       => self.move_to(self.text.len(), shift),
+            KeyCode::End               => self.move_to(self.line_end(self.cursor), shift),
+            KeyCode::Up if self.multi_line   => self.move_to(self.line_up(self.cursor), shift),
+            KeyCode::Down if self.multi_line => self.move_to(self.line_down(self.cursor), shift),
+            _ => return false,
+        }
+
+        self.typing = false;
+        true
+    }
+
+    ///Start of the line pos is on.
+    pub fn line_start(&self, pos: usize) -> usize {
+        self.text[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    ///End of the line pos is on, before its new line.
+    pub fn line_end(&self, pos: usize) -> usize {
+        self.text[pos..].find('\n').map_or(self.text.len(), |i| pos + i)
+    }
+
+    ///Columns the text between the start of the line and pos takes up.
+    pub fn column(&self, pos: usize, mask: Option<char>) -> usize {
+        self.text[self.line_start(pos)..pos].graphemes(true).map(|g| cluster_width(g, mask)).sum()
+    }
+
+    ///The position closest to the column on the line starting at line_start.
+    pub fn at_column(&self, line_start: usize, column: usize, mask: Option<char>) -> usize {
+        let line = &self.text[line_start..self.line_end(line_start)];
+        let mut width = 0;
+
+        for (i, g) in line.grapheme_indices(true) {
+            let next = width + cluster_width(g, mask);
+
+            //rounds to whichever side of the cluster is closer.
+            if next > column {
+                return if column - width < next - column { line_start + i }
+                                                     else { line_start + i + g.len() }
+            }
+
+            width = next;
+        }
+
+        line_start + line.len()
+    }
+
+    ///Turns the text into something that can go in the editor, single lines get spaces for new lines.
+    fn clean(&self, text: &str) -> String {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+        if self.multi_line { text }
+                      else { text.replace('\n', " ") }
+    }
+
+    fn boundary(&self, pos: usize) -> usize {
+        let mut pos = pos.min(self.text.len());
+        if pos == self.text.len() { return pos }
+
+        while !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+
+        self.prev_boundary(self.next_boundary(pos))
+    }
+
+    fn prev_boundary(&self, pos: usize) -> usize {
+        self.text[..pos].grapheme_indices(true).next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, pos: usize) -> usize {
+        pos + self.text[pos..].graphemes(true).next().map_or(0, |g| g.len())
+    }
+
+    ///Start of the word before pos.
+    fn word_left(&self, pos: usize) -> usize {
+        if self.one_word { return 0 }
+
+        let before = &self.text[..pos];
+        let end = before.trim_end_matches(|c: char| !is_word(c)).len();
+        before[..end].trim_end_matches(is_word).len()
+    }
+
+    ///End of the word after pos.
+    fn word_right(&self, pos: usize) -> usize {
+        if self.one_word { return self.text.len() }
+
+        let after = &self.text[pos..];
+        let start = after.len() - after.trim_start_matches(|c: char| !is_word(c)).len();
+        self.text.len() - after[start..].trim_start_matches(is_word).len()
+    }
+
+    fn line_up(&self, pos: usize) -> usize {
+        let start = self.line_start(pos);
+        if start == 0 { return 0 }
+
+        self.at_column(self.line_start(start - 1), self.column(pos, None), None)
+    }
+
+    fn line_down(&self, pos: usize) -> usize {
+        let end = self.line_end(pos);
+        if end == self.text.len() { return end }
+
+        self.at_column(end + 1, self.column(pos, None), None)
+    }
+
+    fn move_to(&mut self, pos: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        }
+        else {
+            self.anchor = None;
+        }
+
+        self.cursor = pos;
+    }
+
+    ///Deletes the selection, or from the cursor to pos if nothing is selected.
+    fn delete_to(&mut self, pos: usize) {
+        if self.selection().is_none() {
+            if pos == self.cursor { return }
+            self.anchor = Some(pos);
+        }
+
+        self.save();
+        self.delete_selection();
+    }
+
+    fn delete_selection(&mut self) {
+        if let Some(range) = self.selection() {
+            self.text.replace_range(range.clone(), "");
+            self.cursor = range.start;
+        }
+
+        self.anchor = None;
+    }
+
+    ///Saves the text before an edit so it can be undone.
+    fn save(&mut self) {
+        self.undo.push(Snapshot { text: self.text.clone(), cursor: self.cursor });
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+
+        self.redo.clear();
+        self.typing = false;
+    }
+
+    fn step(&mut self, undo: bool) -> bool {
+        let (from, to) = if undo { (&mut self.undo, &mut self.redo) }
+                             else { (&mut self.redo, &mut self.undo) };
+
+        let snapshot = match from.pop() {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+
+        to.push(Snapshot { text: std::mem::replace(&mut self.text, snapshot.text), cursor: self.cursor });
+        self.cursor = snapshot.cursor;
+        self.anchor = None;
+        self.typing = false;
+        true
+    }
+
+    ///The pixels of one line from the column scroll, width wide.
+    pub fn row(&self, line_start: usize, scroll: usize, width: i32, look: &Look) -> Vec<Pixel> {
+        let line_end = self.line_end(line_start);
+        let selection = self.selection().unwrap_or(0..0);
+        let mut row = Vec::new();
+        let mut column = 0;
+
+        let mut cell = |row: &mut Vec<Pixel>, c: char, marks: Marks, pos: usize| {
+            let mut colors = if selection.contains(&pos) { look.selected }
+                                                     else { look.default.get_color_set() };
+
+            if look.focused && pos == self.cursor {
+                colors = ColorSet { fg: colors.bg, bg: colors.fg, ..colors };
+            }
+
+            let data = PixelData { marks, ..PixelData::new_color_set(c, colors) };
+            let c_width = data.width() as usize;
+            let end = scroll + width as usize;
+
+            if column >= scroll && column + c_width <= end {
+                row.push(Pixel::Opaque(data));
+                if c_width == 2 { row.push(Pixel::Continuation) }
+            }
+            //half of a wide character sticking out at the edge.
+            else if column + c_width > scroll && column < end {
+                row.push(Pixel::Opaque(data.with_char(' ')));
+            }
+
+            column += c_width;
+        };
+
+        for (i, g) in self.text[line_start..line_end].grapheme_indices(true) {
+            let (c, marks) = match (look.mask, split_cluster(g)) {
+                (Some(mask), _)                        => (mask, Marks::NONE),
+                (None, Some((c, _))) if c.is_control() => (' ', Marks::NONE),
+                (None, Some(split))                    => split,
+                (None, None)                           => continue,
+            };
+
+            cell(&mut row, c, marks, line_start + i);
+        }
+
+        //the cursor at the end of the line.
+        if look.focused && self.cursor == line_end {
+            cell(&mut row, ' ', Marks::NONE, line_end);
+        }
+
+        row.resize(width.max(0) as usize, Pixel::Opaque(look.default));
+        row
+    }
+}
+
+/// How the text of an editor is drawn.
+pub(crate) struct Look {
+    pub default:  PixelData,
+    pub selected: ColorSet,
+    pub mask:     Option<char>,
+    ///The cursor is only shown when focused.
+    pub focused:  bool,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+///Columns a grapheme cluster takes up when drawn by the editor, the same as the pixel it is drawn as.
+fn cluster_width(cluster: &str, mask: Option<char>) -> usize {
+    let width = match mask {
+        Some(mask) => char_width(mask),
+        None       => split_cluster(cluster).map_or(0, |(c, marks)| marked_width(c, marks)),
+    };
+
+    width as usize
+}
+
+///The placeholder drawn in its colors when there is no text, with the cursor on its first character.
+pub(crate) fn placeholder_row(placeholder: &str, colors: ColorSet, look: &Look, width: i32) -> Vec<Pixel> {
+    let width = width.max(0) as usize;
+    let mut row = Vec::new();
+
+    for c in placeholder.chars() {
+        if row.len() + char_width(c) as usize > width { break }
+
+        row.push(Pixel::Opaque(PixelData::new_color_set(c, colors)));
+        if char_width(c) == 2 { row.push(Pixel::Continuation) }
+    }
+
+    if look.focused {
+        let cursor = match row.first() {
+            Some(Pixel::Opaque(data)) => *data,
+            _ => look.default,
+        };
+
+        let cursor = Pixel::Opaque(PixelData { fg: cursor.bg, bg: cursor.fg, ..cursor });
+        match row.first_mut() {
+            Some(first) => *first = cursor,
+            None => row.push(cursor),
+        }
+    }
+
+    row.resize(width, Pixel::Opaque(look.default));
+    row
+}
+
+///Moves scroll as little as possible to show the position, size is the number of rows or columns shown.
+pub(crate) fn scroll_to_show(scroll: usize, pos: usize, size: i32) -> usize {
+    let size = size.max(1) as usize;
+
+    if pos < scroll { pos }
+    else if pos >= scroll + size { pos + 1 - size }
+    else { scroll }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn press(editor: &mut Editor, keys: &[(KeyCode, KeyModifiers)]) {
+        for (code, modifiers) in keys {
+            assert!(editor.handle_key(&key(*code, *modifiers)), "{:?} wasn't used", code);
+        }
+    }
+
+    fn type_text(editor: &mut Editor, text: &str) {
+        for c in text.chars() {
+            editor.handle_key(&key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+    const CTRL: KeyModifiers = KeyModifiers::CONTROL;
+    const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+
+    #[test]
+    fn editing() {
+        let mut editor = Editor::new(false);
+
+        type_text(&mut editor, "hello wörld");
+        press(&mut editor, &[(KeyCode::Left, CTRL), (KeyCode::Backspace, NONE)]);
+        assert_eq!(editor.text, "hellowörld");
+
+        //the e and its accent are one character.
+        editor.insert(" e\u{301}");
+        press(&mut editor, &[(KeyCode::Left, NONE), (KeyCode::Delete, NONE)]);
+        assert_eq!(editor.text, "hello wörld");
+
+        press(&mut editor, &[(KeyCode::Home, NONE), (KeyCode::Right, CTRL), (KeyCode::Right, SHIFT), (KeyCode::End, SHIFT)]);
+        assert_eq!(editor.selection(), Some(5..12));
+        editor.insert("\nthere\r\n");
+        assert_eq!(editor.text, "hello there ");
+
+        press(&mut editor, &[(KeyCode::Backspace, CTRL)]);
+        assert_eq!(editor.text, "hello ");
+
+        //keys that aren't for the editor are passed on.
+        assert!(!editor.handle_key(&key(KeyCode::Enter, NONE)));
+        assert!(!editor.handle_key(&key(KeyCode::Up, NONE)));
+        assert!(!editor.handle_key(&key(KeyCode::Tab, NONE)));
+
+        editor.one_word = true;
+        press(&mut editor, &[(KeyCode::Left, CTRL)]);
+        assert_eq!(editor.cursor, 0);
+    }
+
+    #[test]
+    fn lines_and_undo() {
+        let mut editor = Editor::new(true);
+
+        type_text(&mut editor, "ab");
+        press(&mut editor, &[(KeyCode::Enter, NONE)]);
+        type_text(&mut editor, "世cd");
+        press(&mut editor, &[(KeyCode::Enter, NONE)]);
+        type_text(&mut editor, "e");
+        assert_eq!(editor.text, "ab\n世cd\ne");
+
+        //the wide character takes up two columns.
+        press(&mut editor, &[(KeyCode::Up, NONE), (KeyCode::End, NONE), (KeyCode::Up, NONE)]);
+        assert_eq!(editor.cursor, 2);
+        //halfway through the wide character rounds to after it.
+        press(&mut editor, &[(KeyCode::Left, NONE), (KeyCode::Down, NONE)]);
+        assert_eq!(editor.cursor, 6);
+        press(&mut editor, &[(KeyCode::Down, NONE), (KeyCode::Down, NONE)]);
+        assert_eq!(editor.cursor, editor.text.len());
+
+        //typing is undone a word at a time.
+        assert!(editor.undo());
+        assert_eq!(editor.text, "ab\n世cd\n");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "ab\n世cd");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "ab\n");
+        assert!(editor.redo());
+        assert_eq!(editor.text, "ab\n世cd");
+
+        press(&mut editor, &[(KeyCode::Char('z'), CTRL), (KeyCode::Char('z'), CTRL), (KeyCode::Char('z'), CTRL)]);
+        assert_eq!(editor.text, "");
+        assert!(!editor.undo());
+
+        press(&mut editor, &[(KeyCode::Char('y'), CTRL)]);
+        assert_eq!(editor.text, "ab");
+
+        //an edit after undoing can't be redone past.
+        type_text(&mut editor, "x");
+        assert!(!editor.redo());
+    }
+}
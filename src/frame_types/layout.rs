@@ -1,11 +1,27 @@
 use crate::prelude::*;
 use crate::modifiers::position::Position;
 use crate::frame_types::fill;
-use crate::modifiers::position;
+use crate::modifiers::position::{self, IPosition, PosData};
+use std::collections::{HashMap, VecDeque};
 
 pub struct Object {
     pub frame: Frame,
     pub pos:   Position,
+    ///Objects with a higher z are drawn on top, objects with the same z are drawn in the order they were added.
+    pub z:     i32,
+    ///Only draw the parts of a child object that are inside its parent, does nothing for objects without a parent.
+    pub clip:  bool,
+}
+
+impl Object {
+    pub fn new(frame: Frame, pos: Position) -> Self {
+        Object {
+            frame,
+            pos,
+            z:    0,
+            clip: false,
+        }
+    }
 }
 
 pub type Layout = Rc<RefCell<ILayout>>;
@@ -14,23 +30,101 @@ pub fn new() -> Layout {
     wrap(ILayout::new())
 }
 
+/// A handle to an object in a layout, it stays valid while other objects are added and removed.
+/// Once its object is removed the handle no longer finds anything, even if the slot gets reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    index:      u32,
+    generation: u32,
+}
+
+/// The object found by a hit test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Hit {
+    pub id:    ObjectId,
+    ///Where the point is in the object's frame, with its flips and rotation undone.
+    pub local: Coord,
+}
+
+///Called after each object is drawn with where it is in the layout.
+type Visit<'a> = dyn FnMut(&mut ScreenBuf, ObjectId, &PosData) + 'a;
+
+struct Slot {
+    generation: u32,
+    ///the object and when it was added, used to keep objects with the same z in order.
+    entry:      Option<(u64, Object)>,
+    parent:     Option<ObjectId>,
+}
+
+/// Draws a list of objects each placed by their own position modifier.
+/// Objects can be children of other objects, a child's position is then relative to its parent's frame
+/// so moving, flipping, rotating or hiding the parent does the same to all of its children.
+/// Children are drawn after their parent, ordered by z between themselves.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - add
+/// - add_child
+/// - add_background
+/// - remove
+/// - set_parent
+/// - parent
+/// - children
+/// - hit_test
+/// - get
+/// - get_mut
+/// - set_enabled
+/// - iter
+/// - len
+/// - is_empty
+/// - raise
+/// - lower
+/// - to_front
+/// - to_back
 pub struct ILayout {
-    pub objects: Vec<Object>,
+    slots:    Vec<Slot>,
+    free:     Vec<u32>,
+    next_seq: u64,
 }
 
 impl IFrame for ILayout {
     fn get_draw_data(&self, screenbuf: &mut ScreenBuf, offset: Coord, size: Coord) {
-        for obj in &self.objects {
-            if obj.pos.borrow().data.enabled {
-                screenbuf.use_modifier_on(obj.pos.clone(), &obj.frame, offset, size);
-            }
-        }
+        self.draw_all(screenbuf, offset, size, &mut |_, _, _| {});
     }
 
     fn update(&mut self, new_position: Coord) {
-        for obj in &self.objects {
-            obj.pos.borrow_mut().update(new_position);
-            obj.frame.borrow_mut().update(new_position);
+        let children = self.children_map();
+
+        //roots in the order they were added so an object can be placed relative to one added before it.
+        let mut roots: Vec<(u64, ObjectId)> = self.slots.iter().enumerate()
+            .filter(|(_, slot)| slot.parent.is_none())
+            .filter_map(|(index, slot)| {
+                let (seq, _) = slot.entry.as_ref()?;
+                Some((*seq, ObjectId { index: index as u32, generation: slot.generation }))
+            })
+            .collect();
+
+        roots.sort_by_key(|(seq, _)| *seq);
+
+        //parents first so their children are sized to the new parent.
+        let mut todo: VecDeque<(ObjectId, Coord)> = roots.into_iter()
+            .map(|(_, id)| (id, new_position))
+            .collect();
+
+        while let Some((id, new_size)) = todo.pop_front() {
+            let obj = match self.get(id) {
+                Some(obj) => obj,
+                None => continue,
+            };
+
+            obj.pos.borrow_mut().update(new_size);
+            obj.frame.borrow_mut().update(new_size);
+
+            let size = obj.pos.borrow().data.size;
+            if let Some(list) = children.get(&id) {
+                todo.extend(list.iter().map(|child| (*child, size)));
+            }
         }
     }
 }
@@ -38,19 +132,503 @@ impl IFrame for ILayout {
 impl ILayout {
     pub fn new() -> Self {
         Self {
-            objects: Vec::new()
+            slots:    Vec::new(),
+            free:     Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub fn add(&mut self, obj: Object) -> ObjectId {
+        let entry = Some((self.next_seq, obj));
+        self.next_seq += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.entry = entry;
+            slot.parent = None;
+
+            return ObjectId { index, generation: slot.generation }
         }
+
+        self.slots.push(Slot { generation: 0, entry, parent: None });
+
+        ObjectId { index: (self.slots.len() - 1) as u32, generation: 0 }
     }
 
-    pub fn add_background(&mut self, pixel: Pixel) {
+    ///Adds an object as a child of parent, its position is then relative to the parent's frame.
+    ///Returns None without adding it if the parent is not in the layout.
+    pub fn add_child(&mut self, parent: ObjectId, obj: Object) -> Option<ObjectId> {
+        self.get(parent)?;
+
+        let id = self.add(obj);
+        self.slots[id.index as usize].parent = Some(parent);
+
+        Some(id)
+    }
+
+    ///Adds a fill behind everything currently in the layout.
+    pub fn add_background(&mut self, pixel: Pixel) -> ObjectId {
         let temp = position::new();
         temp.borrow_mut().set_update(position::update_types::MatchSize{});
 
-        self.objects.insert(0, 
-            Object {
-                frame: fill::new(pixel),
-                pos: temp,
+        let id = self.add(Object::new(fill::new(pixel), temp));
+        self.to_back(id);
+        id
+    }
+
+    ///Takes the object out of the layout along with all of its children, None if it was already removed.
+    pub fn remove(&mut self, id: ObjectId) -> Option<Object> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation { return None }
+
+        let (_, obj) = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.parent = None;
+        self.free.push(id.index);
+
+        for child in self.children(id) {
+            self.remove(child);
+        }
+
+        Some(obj)
+    }
+
+    ///Moves the object under a new parent, or to the top level with None.
+    ///Returns false if either object is not in the layout or the parent is inside the object.
+    pub fn set_parent(&mut self, id: ObjectId, parent: Option<ObjectId>) -> bool {
+        if self.get(id).is_none() { return false }
+
+        if let Some(parent) = parent {
+            if self.get(parent).is_none() { return false }
+
+            let mut above = Some(parent);
+            while let Some(next) = above {
+                if next == id { return false }
+                above = self.parent(next);
+            }
+        }
+
+        self.slots[id.index as usize].parent = parent;
+        true
+    }
+
+    pub fn parent(&self, id: ObjectId) -> Option<ObjectId> {
+        self.get(id)?;
+        self.slots[id.index as usize].parent
+    }
+
+    ///The children of the object in the order they are drawn.
+    pub fn children(&self, id: ObjectId) -> Vec<ObjectId> {
+        self.iter().map(|(child, _)| child).filter(|child| self.parent(*child) == Some(id)).collect()
+    }
+
+    ///Finds the topmost object drawn over point in a layout of the given size.
+    ///Without pixel_accurate anywhere inside an object's visible area counts, with it only the places it draws a pixel do,
+    ///so masked out and clear parts of a frame let objects behind them be hit.
+    pub fn hit_test(&self, point: Coord, size: Coord, pixel_accurate: bool) -> Option<Hit> {
+        let mut probe = ScreenBuf::new_probe(size, point);
+        let mut found = None;
+
+        self.draw_all(&mut probe, Coord{x: 0, y: 0}, size, &mut |screenbuf, id, data| {
+            let local = data.local(point);
+            let start = screenbuf.offset();
+            let end   = screenbuf.end();
+
+            let drawn = screenbuf.take_probe();
+            let inside = local.x >= start.x && local.y >= start.y && local.x < end.x && local.y < end.y;
+
+            //objects drawn later are on top.
+            if (pixel_accurate && drawn) || (!pixel_accurate && inside) {
+                found = Some(Hit { id, local });
+            }
+        });
+
+        found
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        let slot = self.slots.get(id.index as usize)?;
+        if slot.generation != id.generation { return None }
+
+        slot.entry.as_ref().map(|(_, obj)| obj)
+    }
+
+    pub fn get_mut(&mut self, id: ObjectId) -> Option<&mut Object> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation { return None }
+
+        slot.entry.as_mut().map(|(_, obj)| obj)
+    }
+
+    ///Shows or hides the object, returns false if it is not in the layout.
+    pub fn set_enabled(&mut self, id: ObjectId, enabled: bool) -> bool {
+        match self.get(id) {
+            Some(obj) => {
+                obj.pos.borrow_mut().data.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///All of the objects in the order they are drawn, back to front.
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectId, &Object)> {
+        let mut order: Vec<(i32, u64, ObjectId, &Object)> = self.slots.iter().enumerate()
+            .filter_map(|(index, slot)| {
+                let (seq, obj) = slot.entry.as_ref()?;
+                Some((obj.z, *seq, ObjectId { index: index as u32, generation: slot.generation }, obj))
+            })
+            .collect();
+
+        order.sort_by_key(|(z, seq, _, _)| (*z, *seq));
+
+        order.into_iter().map(|(_, _, id, obj)| (id, obj))
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///The children of every object that has any, each list in draw order.
+    fn children_map(&self) -> HashMap<ObjectId, Vec<ObjectId>> {
+        let mut map: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+
+        for (id, _) in self.iter() {
+            if let Some(parent) = self.parent(id) {
+                map.entry(parent).or_default().push(id);
+            }
+        }
+
+        map
+    }
+
+    ///Draws every object in order, calling visit after each one.
+    fn draw_all(&self, screenbuf: &mut ScreenBuf, offset: Coord, size: Coord, visit: &mut Visit) {
+        let children = self.children_map();
+
+        for (id, obj) in self.iter() {
+            if self.parent(id).is_some() { continue }
+
+            //children that aren't clipped can't be drawn inside their parent, so they are drawn after it on their own.
+            let mut unclipped = Vec::new();
+            let data = obj.pos.borrow().data;
+            self.draw_tree(screenbuf, &children, id, obj.pos.clone(), data, offset, size, &mut unclipped, visit);
+
+            while !unclipped.is_empty() {
+                for (id, data) in std::mem::take(&mut unclipped) {
+                    let pos = wrap(IPosition::with_data(data));
+                    self.draw_tree(screenbuf, &children, id, pos, data, offset, size, &mut unclipped, visit);
+                }
             }
-        )
+        }
+    }
+
+    ///Draws the object through pos along with its clipped children, data is where the object really is in the layout.
+    ///Unclipped children are added to unclipped with where they are in the layout, to be drawn outside of the parent.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_tree(&self, screenbuf: &mut ScreenBuf, children: &HashMap<ObjectId, Vec<ObjectId>>, id: ObjectId, pos: Position,
+                 data: PosData, offset: Coord, size: Coord, unclipped: &mut Vec<(ObjectId, PosData)>, visit: &mut Visit) {
+        let obj = match self.get(id) {
+            Some(obj) => obj,
+            None => return,
+        };
+
+        if !data.enabled { return }
+
+        screenbuf.use_modifier_with(pos, offset, size, |screenbuf, offset, size| {
+            if size.x > 0 && size.y > 0 {
+                obj.frame.borrow().get_draw_data(screenbuf, offset, size);
+            }
+
+            visit(screenbuf, id, &data);
+
+            for child_id in children.get(&id).into_iter().flatten() {
+                let child = match self.get(*child_id) {
+                    Some(child) => child,
+                    None => continue,
+                };
+
+                let child_data = data.compose(&child.pos.borrow().data);
+
+                if child.clip {
+                    self.draw_tree(screenbuf, children, *child_id, child.pos.clone(), child_data, offset, size, unclipped, visit);
+                }
+                else {
+                    unclipped.push((*child_id, child_data));
+                }
+            }
+        });
+    }
+
+    ///The highest and lowest z of every object except id.
+    fn z_range(&self, id: ObjectId) -> Option<(i32, i32)> {
+        let mut others = self.iter().filter(|(other, _)| *other != id).map(|(_, obj)| obj.z);
+        let first = others.next()?;
+
+        Some(others.fold((first, first), |(low, high), z| (low.min(z), high.max(z))))
+    }
+
+    ///Moves the object up one z, returns false if it is not in the layout.
+    pub fn raise(&mut self, id: ObjectId) -> bool {
+        match self.get_mut(id) {
+            Some(obj) => { obj.z += 1; true }
+            None => false,
+        }
+    }
+
+    ///Moves the object down one z, returns false if it is not in the layout.
+    pub fn lower(&mut self, id: ObjectId) -> bool {
+        match self.get_mut(id) {
+            Some(obj) => { obj.z -= 1; true }
+            None => false,
+        }
+    }
+
+    ///Puts the object above every other object, returns false if it is not in the layout.
+    pub fn to_front(&mut self, id: ObjectId) -> bool {
+        let range = self.z_range(id);
+
+        match self.get_mut(id) {
+            Some(obj) => {
+                if let Some((_, high)) = range {
+                    obj.z = high + 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///Puts the object below every other object, returns false if it is not in the layout.
+    pub fn to_back(&mut self, id: ObjectId) -> bool {
+        let range = self.z_range(id);
+
+        match self.get_mut(id) {
+            Some(obj) => {
+                if let Some((low, _)) = range {
+                    obj.z = low - 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::frame_types::basic;
+
+    fn square(layout: &mut ILayout, c: char, pos: Coord) -> ObjectId {
+        let pos = position::craft().pos(pos).size(Coord{x: 2, y: 2}).done();
+        layout.add(Object::new(fill::new(Pixel::new(c, Color::White, Color::Black)), pos))
+    }
+
+    fn draw(layout: &mut ILayout) -> ScreenBuf {
+        layout.update(Coord{x: 4, y: 3});
+
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 3});
+        layout.get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 4, y: 3});
+        buf
+    }
+
+    #[test]
+    fn z_order() {
+        let mut layout = ILayout::new();
+        let a = square(&mut layout, 'a', Coord{x: 0, y: 0});
+        let b = square(&mut layout, 'b', Coord{x: 1, y: 1});
+        let c = square(&mut layout, 'c', Coord{x: 2, y: 0});
+        layout.add_background(Pixel::new('.', Color::White, Color::Black));
+
+        //same z keeps the order they were added.
+        assert_chars(&draw(&mut layout).buffer, "
+aacc
+abcc
+.bb.");
+
+        assert!(layout.to_front(a));
+        assert_chars(&draw(&mut layout).buffer, "
+aacc
+aacc
+.bb.");
+
+        assert!(layout.raise(b));
+        assert!(layout.raise(b));
+        assert_chars(&draw(&mut layout).buffer, "
+aacc
+abbc
+.bb.");
+
+        assert!(layout.to_back(b));
+        assert_chars(&draw(&mut layout).buffer, "
+aacc
+aacc
+....");
+
+        //c now has the same z as the background, which was added after it.
+        assert!(layout.lower(c));
+        assert_chars(&draw(&mut layout).buffer, "
+aa..
+aa..
+....");
+
+    }
+
+    #[test]
+    fn children() {
+        let mut layout = ILayout::new();
+        let parent = square(&mut layout, 'p', Coord{x: 1, y: 0});
+
+        let pos = position::craft().pos(Coord{x: 1, y: 1}).size(Coord{x: 2, y: 1}).done();
+        let child = layout.add_child(parent, Object::new(fill::new(Pixel::new('c', Color::White, Color::Black)), pos.clone())).unwrap();
+
+        assert_eq!(layout.parent(child), Some(parent));
+        assert_eq!(layout.children(parent), vec![child]);
+        assert!(!layout.set_parent(parent, Some(child)));
+
+        assert_chars(&draw(&mut layout).buffer, "
+ pp 
+ pcc
+    ");
+
+        //clipped to the parent.
+        layout.get_mut(child).unwrap().clip = true;
+        assert_chars(&draw(&mut layout).buffer, "
+ pp 
+ pc 
+    ");
+
+        //moving and flipping the parent takes the child with it.
+        layout.get_mut(child).unwrap().clip = false;
+        {
+            let obj = layout.get(parent).unwrap();
+            let mut temp = obj.pos.borrow_mut();
+            temp.data.pos = Coord{x: 2, y: 1};
+            temp.flipx();
+        }
+        assert_chars(&draw(&mut layout).buffer, "
+    
+  pp
+ ccp");
+
+        //hiding the parent hides the child.
+        assert!(layout.set_enabled(parent, false));
+        assert_chars(&draw(&mut layout).buffer, "
+    
+    
+    ");
+
+        assert!(layout.remove(parent).is_some());
+        assert!(layout.get(child).is_none());
+        assert!(layout.is_empty());
+    }
+
+    #[test]
+    fn compose() {
+        let parent = position::craft().pos(Coord{x: 3, y: 1}).size(Coord{x: 4, y: 3}).done();
+        parent.borrow_mut().rot_cw();
+
+        let child = position::craft().pos(Coord{x: 1, y: 0}).size(Coord{x: 2, y: 1}).done();
+        child.borrow_mut().flipy();
+
+        let world = parent.borrow().data.compose(&child.borrow().data);
+        let (parent, child) = (parent.borrow().data, child.borrow().data);
+
+        for x in 0..2 {
+            assert_eq!(world.place(Coord{x, y: 0}), parent.place(child.place(Coord{x, y: 0})));
+        }
+    }
+
+    #[test]
+    fn hit_test() {
+        let mut layout = ILayout::new();
+        let a = square(&mut layout, 'a', Coord{x: 0, y: 0});
+
+        //a ring with a clear middle over a.
+        let c = Pixel::Clear;
+        let r = Pixel::new('r', Color::White, Color::Black);
+        let ring = basic::new(Coord{x: 3, y: 3}, vec![r,r,r, r,c,r, r,r,r]).unwrap();
+        let pos = position::craft().size(Coord{x: 3, y: 3}).done();
+        let b = layout.add(Object::new(ring, pos));
+
+        let size = Coord{x: 4, y: 3};
+
+        assert_eq!(layout.hit_test(Coord{x: 1, y: 1}, size, false), Some(Hit { id: b, local: Coord{x: 1, y: 1} }));
+        assert_eq!(layout.hit_test(Coord{x: 1, y: 1}, size, true), Some(Hit { id: a, local: Coord{x: 1, y: 1} }));
+        assert_eq!(layout.hit_test(Coord{x: 3, y: 2}, size, false), None);
+
+        //local coordinates undo flips and rotations.
+        layout.get(b).unwrap().pos.borrow_mut().rot_cw();
+        assert_eq!(layout.hit_test(Coord{x: 2, y: 0}, size, true), Some(Hit { id: b, local: Coord{x: 0, y: 0} }));
+
+        //children can be hit, and clipping hides the parts outside the parent.
+        let pos = position::craft().pos(Coord{x: 1, y: 1}).size(Coord{x: 2, y: 2}).done();
+        let child = layout.add_child(a, Object { clip: true, ..Object::new(fill::new(r), pos) }).unwrap();
+        layout.to_front(a);
+
+        assert_eq!(layout.hit_test(Coord{x: 1, y: 1}, size, true), Some(Hit { id: child, local: Coord{x: 0, y: 0} }));
+        assert_eq!(layout.hit_test(Coord{x: 2, y: 2}, size, false), Some(Hit { id: b, local: Coord{x: 2, y: 0} }));
+
+        layout.set_enabled(b, false);
+        assert_eq!(layout.hit_test(Coord{x: 2, y: 2}, size, true), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn handles() {
+        let mut layout = ILayout::new();
+        let a = square(&mut layout, 'a', Coord{x: 0, y: 0});
+        let b = square(&mut layout, 'b', Coord{x: 1, y: 1});
+
+        assert_eq!(layout.len(), 2);
+        assert!(layout.remove(a).is_some());
+        assert!(layout.remove(a).is_none());
+        assert!(layout.get(a).is_none());
+        assert!(!layout.raise(a));
+
+        //reuses the slot a was in, but a still finds nothing.
+        let c = square(&mut layout, 'c', Coord{x: 2, y: 0});
+        assert!(layout.get(a).is_none());
+        assert!(layout.get(c).is_some());
+        assert_eq!(layout.len(), 2);
+
+        //added after b so drawn over it.
+        assert_eq!(layout.iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![b, c]);
+
+        assert!(layout.set_enabled(b, false));
+        layout.get_mut(c).unwrap().pos.borrow_mut().data.pos = Coord{x: 1, y: 0};
+        assert_chars(&draw(&mut layout).buffer, "
+ cc 
+ cc 
+    ");
+    }
+
+    #[test]
+    fn update_order() {
+        use crate::modifiers::position::update_types::{Anchor, Point, RelativeTo};
+
+        let mut layout = ILayout::new();
+
+        let target = position::craft().size(Coord{x: 10, y: 4}).update(Anchor::new(Point::Center)).done();
+        layout.add(Object::new(fill::new(Pixel::new('t', Color::White, Color::Black)), target.clone()));
+
+        //added after its target, so it is placed from where the target is moved to in the same update.
+        let follower = position::craft().size(Coord{x: 2, y: 2}).update(RelativeTo {
+            target:       target.clone(),
+            target_point: Point::BottomLeft,
+            point:        Point::TopLeft,
+            offset:       Coord{x: 0, y: 0},
+        }).done();
+        layout.add(Object::new(fill::new(Pixel::new('f', Color::White, Color::Black)), follower.clone()));
+
+        layout.update(Coord{x: 80, y: 24});
+
+        assert_eq!(target.borrow().data.pos, Coord{x: 35, y: 10});
+        assert_eq!(follower.borrow().data.pos, Coord{x: 35, y: 14});
+    }
+}
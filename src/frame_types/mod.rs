@@ -5,6 +5,9 @@
 //! - Text
 //! - Bundle
 //! - Mask
+//! - Split
+//! - Text Input
+//! - Text Area
 
 pub mod basic;
 
@@ -16,4 +19,12 @@ pub mod bundle;
 
 pub mod layout;
 
-pub mod with_modifier;
\ No newline at end of file
+pub mod with_modifier;
+
+pub mod split;
+
+pub mod text_input;
+
+pub mod text_area;
+
+mod editor;
\ No newline at end of file
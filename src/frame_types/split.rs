@@ -0,0 +1,282 @@
+use crate::prelude::*;
+use crate::modifiers::position::{self, Position};
+
+pub type Split = Rc<RefCell<ISplit>>;
+
+pub fn new(direction: Direction) -> Split {
+    wrap(ISplit::new(direction))
+}
+
+pub fn craft(direction: Direction) -> CraftSplit {
+    CraftSplit(ISplit::new(direction))
+}
+
+pub struct CraftSplit (ISplit);
+
+impl CraftSplit {
+    pub fn add(mut self, constraint: Constraint, frame: Frame) -> Self {
+        self.0.push(constraint, frame);
+        self
+    }
+
+    pub fn done(self) -> Split {
+        wrap(self.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    ///Children are placed left to right.
+    Horizontal,
+    ///Children are placed top to bottom.
+    Vertical,
+}
+
+/// How much of the split a child gets, along the direction of the split.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    ///Exactly this many cells.
+    Length(i32),
+    ///This percent of the whole split.
+    Percentage(u16),
+    ///This fraction of the whole split.
+    Ratio(u32, u32),
+    ///At least this many cells, grows like Fill(1) when there is space left.
+    Min(i32),
+    ///Grows like Fill(1) but never past this many cells.
+    Max(i32),
+    ///Shares whatever space is left with the other fills, by weight.
+    Fill(u16),
+}
+
+impl Constraint {
+    ///Size before any left over space is shared out.
+    fn base(&self, length: i32) -> i32 {
+        match *self {
+            Constraint::Length(x)     => x,
+            Constraint::Percentage(p) => length * p as i32 / 100,
+            Constraint::Ratio(_, 0)   => 0,
+            Constraint::Ratio(a, b)   => (length as i64 * a as i64 / b as i64) as i32,
+            Constraint::Min(x)        => x,
+            Constraint::Max(_)        => 0,
+            Constraint::Fill(_)       => 0,
+        }.max(0)
+    }
+
+    ///Share of the left over space, 0 for fixed sizes.
+    fn weight(&self) -> u32 {
+        match *self {
+            Constraint::Min(_) | Constraint::Max(_) => 1,
+            Constraint::Fill(w) => w as u32,
+            _ => 0,
+        }
+    }
+
+    fn cap(&self) -> Option<i32> {
+        match *self {
+            Constraint::Max(x) => Some(x.max(0)),
+            _ => None,
+        }
+    }
+}
+
+///Splits length between the constraints.
+///Left over space is shared by weight, with Max children that hit their cap dropping out and leaving the rest to the others.
+///If the constraints ask for more than there is the later children are cut short.
+pub fn solve(constraints: &[Constraint], length: i32) -> Vec<i32> {
+    let length = length.max(0);
+    let mut sizes: Vec<i32> = constraints.iter().map(|c| c.base(length)).collect();
+
+    let mut left = length - sizes.iter().sum::<i32>();
+    let mut growing: Vec<usize> = (0..constraints.len()).filter(|i| constraints[*i].weight() > 0).collect();
+
+    while left > 0 && !growing.is_empty() {
+        let total: u32 = growing.iter().map(|i| constraints[*i].weight()).sum();
+
+        //give capped children what they can take first, then share out again.
+        let capped: Vec<usize> = growing.iter().copied().filter(|i| {
+            match constraints[*i].cap() {
+                Some(cap) => sizes[*i] + share(left, constraints[*i].weight(), total) >= cap,
+                None => false,
+            }
+        }).collect();
+
+        if !capped.is_empty() {
+            for i in capped {
+                let cap = constraints[i].cap().unwrap();
+                left -= cap - sizes[i];
+                sizes[i] = cap;
+                growing.retain(|x| *x != i);
+            }
+            continue
+        }
+
+        let mut given = 0;
+        for i in &growing {
+            let extra = share(left, constraints[*i].weight(), total);
+            sizes[*i] += extra;
+            given += extra;
+        }
+
+        //rounding leftovers go one at a time to the first children.
+        for i in growing.iter().take((left - given) as usize) {
+            sizes[*i] += 1;
+        }
+
+        left = 0;
+    }
+
+    let mut used = 0;
+    for size in &mut sizes {
+        *size = (*size).min(length - used);
+        used += *size;
+    }
+
+    sizes
+}
+
+fn share(left: i32, weight: u32, total: u32) -> i32 {
+    if total == 0 { return 0 }
+
+    (left as i64 * weight as i64 / total as i64) as i32
+}
+
+struct Child {
+    constraint: Constraint,
+    frame:      Frame,
+    pos:        Position,
+}
+
+/// Splits its area into rows or columns sized by constraints, splits can be nested to build up a whole screen.
+/// The area is split up in update, which also updates each child with the size it was given.
+/// ## Functions
+/// - new
+/// - craft
+///
+/// ## Methods
+/// - push
+/// - set_constraint
+/// - len
+/// - is_empty
+pub struct ISplit {
+    pub direction: Direction,
+    children:      Vec<Child>,
+}
+
+impl IFrame for ISplit {
+    fn get_draw_data(&self, screenbuf: &mut ScreenBuf, offset: Coord, size: Coord) {
+        for child in &self.children {
+            screenbuf.use_modifier_on(child.pos.clone(), &child.frame, offset, size);
+        }
+    }
+
+    fn update(&mut self, new_size: Coord) {
+        let constraints: Vec<Constraint> = self.children.iter().map(|child| child.constraint).collect();
+
+        let length = match self.direction {
+            Direction::Horizontal => new_size.x,
+            Direction::Vertical   => new_size.y,
+        };
+
+        let mut start = 0;
+        for (child, length) in self.children.iter().zip(solve(&constraints, length)) {
+            let (pos, size) = match self.direction {
+                Direction::Horizontal => (Coord{x: start, y: 0}, Coord{x: length, y: new_size.y}),
+                Direction::Vertical   => (Coord{x: 0, y: start}, Coord{x: new_size.x, y: length}),
+            };
+            start += length;
+
+            {
+                let mut temp = child.pos.borrow_mut();
+                temp.data.pos = pos;
+                temp.data.size = size;
+            }
+
+            child.frame.borrow_mut().update(size);
+        }
+    }
+}
+
+impl ISplit {
+    pub fn new(direction: Direction) -> Self {
+        ISplit {
+            direction,
+            children: Vec::new(),
+        }
+    }
+
+    ///Adds a child after the others, it gets its size on the next update.
+    pub fn push(&mut self, constraint: Constraint, frame: Frame) {
+        self.children.push(Child {
+            constraint,
+            frame,
+            pos: position::new(),
+        });
+    }
+
+    ///Changes the constraint of the child at index, it is resized on the next update.
+    pub fn set_constraint(&mut self, index: usize, constraint: Constraint) {
+        self.children[index].constraint = constraint;
+    }
+
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::fill;
+    use crate::test_helpers::*;
+    use Constraint::*;
+
+    fn fill(c: char) -> Frame {
+        fill::new(Pixel::new(c, Color::White, Color::Black))
+    }
+
+    #[test]
+    fn solver() {
+        assert_eq!(solve(&[Length(3), Fill(1), Length(2)], 10), vec![3, 5, 2]);
+        assert_eq!(solve(&[Percentage(50), Ratio(1, 4), Fill(1)], 20), vec![10, 5, 5]);
+        assert_eq!(solve(&[Fill(1), Fill(2)], 10), vec![4, 6]);
+        assert_eq!(solve(&[Min(4), Fill(1)], 6), vec![5, 1]);
+        assert_eq!(solve(&[Max(2), Fill(1)], 10), vec![2, 8]);
+        assert_eq!(solve(&[Max(2), Max(3), Fill(2)], 10), vec![2, 3, 5]);
+
+        //asking for too much cuts the later ones short.
+        assert_eq!(solve(&[Length(6), Min(3), Length(4)], 8), vec![6, 2, 0]);
+        assert_eq!(solve(&[Fill(1)], 0), vec![0]);
+    }
+
+    #[test]
+    fn nested() {
+        let body = craft(Direction::Horizontal)
+            .add(Length(2), fill('s'))
+            .add(Fill(1), fill('c'))
+            .done();
+
+        let screen = craft(Direction::Vertical)
+            .add(Length(1), fill('h'))
+            .add(Min(1), body)
+            .add(Length(1), fill('f'))
+            .done();
+
+        let size = Coord{x: 6, y: 5};
+        screen.borrow_mut().update(size);
+
+        let mut buf = ScreenBuf::new(size);
+        screen.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+
+        assert_chars(&buf.buffer, "
+hhhhhh
+sscccc
+sscccc
+sscccc
+ffffff");
+    }
+}
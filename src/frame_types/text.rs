@@ -1,10 +1,15 @@
 use crate::prelude::*;
 use crate::ColorString;
+use crate::input::{EventHandler, Event, EventResult};
+
+use crossterm::event::{KeyCode, MouseEventKind};
 
 use std::collections::VecDeque;
-use std::str::from_utf8;
+
+use unicode_segmentation::UnicodeSegmentation;
 
 use std::cmp::{max, min};
+use std::cell::Cell;
 
 pub enum Indent {
     Normal(usize),
@@ -27,45 +32,139 @@ impl Indent {
     }
 }
 
+/// How lines too long for the width of a Text are broken up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    ///Breaks at the last column that fits, even in the middle of a word.
+    Char,
+    ///Breaks at spaces, words longer than a whole line are broken where they reach the edge.
+    Word,
+    ///Breaks at spaces, splitting words with a hyphen when there is room for part of them.
+    Hyphenate,
+    ///Doesn't wrap, cutting off the end of each line.
+    Truncate,
+    ///Doesn't wrap, ending lines that were cut off with an ellipsis.
+    Ellipsis,
+}
+
+/// Where the lines of an entry go within the width left after the indent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+    ///Spreads out the words of wrapped lines to fill the width, the last line of each paragraph is left aligned.
+    Justify,
+}
+
 pub type Text = Rc<RefCell<IText>>;
 
 pub fn new() -> Text {
     wrap(IText::new())
 }
 
+///Lines moved by each turn of the mouse wheel.
+const WHEEL_LINES: i32 = 3;
+
+/// How the scrollbar along the right edge of a Text is drawn, it takes a column away from the text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scrollbar {
+    pub track: PixelData,
+    pub thumb: PixelData,
+}
+
+impl Scrollbar {
+    pub fn new(track: PixelData, thumb: PixelData) -> Self {
+        Scrollbar { track, thumb }
+    }
+}
+
+impl Default for Scrollbar {
+    fn default() -> Self {
+        Scrollbar {
+            track: PixelData::new('│', Color::Rgb{r: 128, g: 128, b: 128}, Color::Rgb{r: 0, g: 0, b: 0}),
+            thumb: PixelData::new('█', Color::Rgb{r: 255, g: 255, b: 255}, Color::Rgb{r: 0, g: 0, b: 0}),
+        }
+    }
+}
+
 /// Contains a queue of text entries that each have their own color
+/// It is scrolled by wrapped lines, the size it was last drawn at is used to work out how far it can scroll.
+/// Scrolling before the first draw is kept as asked for and only limited once the size is known.
+/// An offset passed to get_draw_data still skips entries with y and lines with x on top of the scroll.
 /// ## Functions
 /// - new
 /// 
 /// ## Methods
+/// - total_height
+/// - height_at
+/// - max_scroll
+/// - scroll_pos
+/// - scroll_to
+/// - scroll_by
+/// - page_up
+/// - page_down
+/// - home
+/// - end
 pub struct IText {
     pub tab_spaces: usize,
     ///Positive indent is hanging, negative is normal indent.
     pub indent:     Indent,
     pub default:    PixelData,
     pub entries:    VecDeque<Entry>,
+    ///Char by default.
+    pub wrap:       Wrap,
+    pub scrollbar:  Option<Scrollbar>,
+    ///Keeps the last line in view as entries are added, as long as it hasn't been scrolled away from the end.
+    pub follow:     bool,
+    ///top line asked for, limited to the lines there are when drawn.
+    scroll:         Cell<usize>,
+    ///whether the scroll was at the bottom when last set or drawn.
+    at_end:         Cell<bool>,
+    ///size of the last draw, None until the first.
+    view:           Cell<Option<Coord>>,
 }
 
 impl IFrame for IText {
     fn get_draw_data(&self, screenbuf: &mut ScreenBuf, offset: Coord, size: Coord) {
+        self.view.set(Some(size));
+
+        //wrapping is the slow part, heights are remembered so only new or changed entries get wrapped.
+        let width = self.text_width(size.x);
+        let total = self.height_at(width);
+        let scroll = self.scroll_for(total);
+
+        self.scroll.set(scroll);
+        self.at_end.set(scroll == self.max_scroll_for(total));
+
         let skip = 0 - (min(offset.y, 0) + min(offset.x, 0));
         let offset = Coord {
-            x: max(offset.x, 0),
+            x: max(offset.x, 0) + scroll as i32,
             y: max(offset.y, 0),
         };
 
-        let mut data = EntryIter::new(&self.entries, self.default, offset, size, &self.indent, self.tab_spaces);
+        let mut data = EntryIter::new(&self.entries, self.default, offset, Coord{x: width, y: size.y}, &self.indent, self.tab_spaces, self.wrap);
 
-        let mut draw_to = screenbuf.draw_to();
+        let thumb = self.thumb(size.y, total, scroll);
+        let start = screenbuf.offset();
 
-        for _ in 0..(skip * size.x) {
-            if let Some(pos) = draw_to.next() {
-                screenbuf.set(pos, Pixel::Opaque(self.default));
+        for pos in screenbuf.draw_to() {
+            let local = pos - start;
+
+            if let Some(bar) = self.scrollbar {
+                if local.x >= width {
+                    let (thumb_start, thumb_len) = thumb;
+                    let on_thumb = local.y >= thumb_start && local.y < thumb_start + thumb_len;
+
+                    screenbuf.set(pos, Pixel::Opaque(if on_thumb { bar.thumb } else { bar.track }));
+                    continue
+                }
             }
-        }
 
-        for pos in draw_to {
-            if let Some(pixel) = data.next() {
+            if local.y < skip {
+                screenbuf.set(pos, Pixel::Opaque(self.default));
+            }
+            else if let Some(pixel) = data.next() {
                 screenbuf.set(pos, pixel);
             }
             else {
@@ -81,22 +180,159 @@ impl IText {
         IText {
             tab_spaces: 4,
             indent:     Indent::Hanging(0),
-            default:    PixelData {
-                character: ' ',
-                fg:        Color::Rgb{r: 255, g: 255, b: 255},
-                bg:        Color::Rgb{r:   0, g:   0, b:   0},
-            },
+            default:    PixelData::new(
+                ' ',
+                Color::Rgb{r: 255, g: 255, b: 255},
+                Color::Rgb{r:   0, g:   0, b:   0},
+            ),
             entries:    VecDeque::new(),
+            wrap:       Wrap::Char,
+            scrollbar:  None,
+            follow:     false,
+            scroll:     Cell::new(0),
+            at_end:     Cell::new(true),
+            view:       Cell::new(None),
+        }
+    }
+
+    ///Number of lines the entries wrap to at the width the text was last drawn at, 0 before it is drawn.
+    pub fn total_height(&self) -> usize {
+        match self.view.get() {
+            Some(view) => self.height_at(self.text_width(view.x)),
+            None       => 0,
+        }
+    }
+
+    ///Number of lines the entries wrap to when width columns are left for the text.
+    ///Each entry remembers its height, so only entries that changed since the last call are wrapped again.
+    pub fn height_at(&self, width: i32) -> usize {
+        if width <= 0 { return 0 }
+
+        let data = EntryIter::new(&self.entries, self.default, Coord{x: 0, y: 0}, Coord{x: width, y: 0}, &self.indent, self.tab_spaces, self.wrap);
+
+        (0..self.entries.len()).map(|i| data.height(i)).sum()
+    }
+
+    ///The furthest down the text can be scrolled with the last line at the bottom.
+    pub fn max_scroll(&self) -> usize {
+        self.max_scroll_for(self.total_height())
+    }
+
+    ///The line shown at the top, or the line asked for before the first draw.
+    pub fn scroll_pos(&self) -> usize {
+        self.scroll_for(self.total_height())
+    }
+
+    pub fn scroll_to(&mut self, line: usize) {
+        self.set_scroll(self.total_height(), line);
+    }
+
+    ///Scrolls down by lines, or up when lines is negative.
+    pub fn scroll_by(&mut self, lines: i32) {
+        let total = self.total_height();
+        let line = self.scroll_for(total) as i64 + lines as i64;
+
+        self.set_scroll(total, max(line, 0) as usize);
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_by(-self.page());
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_by(self.page());
+    }
+
+    pub fn home(&mut self) {
+        self.scroll_to(0);
+    }
+
+    ///Scrolls to the last line, which starts following again when follow is set.
+    pub fn end(&mut self) {
+        self.scroll_to(usize::MAX);
+    }
+
+    fn page(&self) -> i32 {
+        max(self.view.get().map_or(0, |view| view.y), 1)
+    }
+
+    fn text_width(&self, width: i32) -> i32 {
+        match self.scrollbar {
+            Some(_) => width - 1,
+            None    => width,
         }
     }
+
+    fn max_scroll_for(&self, total: usize) -> usize {
+        let rows = self.view.get().map_or(0, |view| max(view.y, 0));
+        total.saturating_sub(rows as usize)
+    }
+
+    ///Keeps the line as it is, the next draw limits it to the lines there are.
+    fn set_scroll(&mut self, total: usize, line: usize) {
+        self.scroll.set(line);
+        self.at_end.set(self.view.get().is_some() && line >= self.max_scroll_for(total));
+    }
+
+    ///The line shown at the top when the entries wrap to total lines, nothing limits it before the first draw.
+    fn scroll_for(&self, total: usize) -> usize {
+        if self.view.get().is_none() { return self.scroll.get() }
+
+        let max_scroll = self.max_scroll_for(total);
+
+        if self.follow && self.at_end.get() { max_scroll }
+                                       else { min(self.scroll.get(), max_scroll) }
+    }
+
+    ///The first row and number of rows of the scrollbar's thumb, for a text total lines long scrolled to scroll.
+    fn thumb(&self, rows: i32, total: usize, scroll: usize) -> (i32, i32) {
+        let total = total as i64;
+        let rows = max(rows, 0) as i64;
+
+        if total <= rows { return (0, rows as i32) }
+
+        let len = max(rows * rows / total, 1);
+        let max_scroll = total - rows;
+        let top = min(scroll as i64, max_scroll);
+
+        (((top * (rows - len) + max_scroll / 2) / max_scroll) as i32, len as i32)
+    }
+}
+
+///Scrolls with the arrow keys, page up and down, home and end, and the mouse wheel.
+impl EventHandler for IText {
+    fn handle_event(&mut self, event: &Event) -> EventResult {
+        match event.input {
+            Input::KeyBoard(e) => match e.code {
+                KeyCode::Up       => self.scroll_by(-1),
+                KeyCode::Down     => self.scroll_by(1),
+                KeyCode::PageUp   => self.page_up(),
+                KeyCode::PageDown => self.page_down(),
+                KeyCode::Home     => self.home(),
+                KeyCode::End      => self.end(),
+                _ => return EventResult::Ignored,
+            },
+            Input::Mouse(e) => match e.kind {
+                MouseEventKind::ScrollUp   => self.scroll_by(-WHEEL_LINES),
+                MouseEventKind::ScrollDown => self.scroll_by(WHEEL_LINES),
+                _ => return EventResult::Ignored,
+            },
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed
+    }
 }
 
 pub struct Entry {
     text:       ColorString,
     len:        usize,
+    ///lines the text wrapped to last time it was laid out, and what it was wrapped with.
+    height:     Cell<Option<(WrapKey, usize)>>,
     //new_lines:  usize,
     //tabs:       usize,
     pub colors: Option<ColorSet>,
+    pub align:  Align,
 }
 
 impl Entry {
@@ -105,10 +341,12 @@ impl Entry {
 
         Entry {
             len:       text.string.chars().count(),
+            height:    Cell::new(None),
             //new_lines: text.string.matches("\n").count(),
             //tabs:      text.string.matches("\t").count(),
             text,
             colors:    None,
+            align:     Align::Left,
         }
     }
 
@@ -117,18 +355,27 @@ impl Entry {
 
         Entry {
             len:       text.string.chars().count(),
+            height:    Cell::new(None),
             //new_lines: text.string.matches("\n").count(),
             //tabs:      text.string.matches("\t").count(),
             text,
             colors:    Some(colors),
+            align:     Align::Left,
         }
     }
 
+    ///Returns the entry with its lines aligned.
+    pub fn aligned(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
     pub fn set_text<T: Into<ColorString>>(&mut self, text: T) {
         let text: ColorString = text.into();
 
         self.text      = text;
         self.len       = self.text.string.chars().count();
+        self.height    = Cell::new(None);
         //self.new_lines = self.text.string.matches("\n").count();
         //self.tabs      = self.text.string.matches("\t").count();
     }
@@ -174,6 +421,8 @@ struct CharIter<'a> {
     cur_entry:    usize,
     byte_index:   usize,
     string_index: usize,
+    ///char index of the start of the last cluster returned, used to look up its colors.
+    char_index:   usize,
 }
 
 impl<'a> CharIter<'a> {
@@ -183,267 +432,464 @@ impl<'a> CharIter<'a> {
             cur_entry: offset,
             byte_index: 0,
             string_index: 0,
+            char_index: 0,
         }
     }
 
-    ///trying to iterate over all of the chars of the current entry without having to scan through each char each time.
-    fn next(&mut self) -> Option<char> {
-        if let Some(entry) = self.entries.get(self.cur_entry) {
+    ///iterates over the grapheme clusters of the current entry, returning the character and marks each one is drawn as.
+    fn next(&mut self) -> Option<(char, Marks)> {
+        loop {
+            let entry = self.entries.get(self.cur_entry)?;
+
             //only check for chars if there a some left in the string.
-            if self.string_index < entry.len {
-                //get a byte slice of the string.
-                let mut byte_end = self.byte_index + 1;
-                loop {
-                    //if the byte slice contains a full char return or extend the slice and check until it does.
-                    if let Ok(c) = from_utf8(&entry.text.string.as_bytes()[self.byte_index..byte_end]) {
-                        self.byte_index = byte_end;
-                        self.string_index += 1;
-
-                        return Some(c.chars().next().unwrap())
-                    }
-                    else {
-                        byte_end += 1;
-                    }
-                }
-                
+            if self.string_index >= entry.len { return None }
+
+            let cluster = entry.text.string[self.byte_index..].graphemes(true).next()?;
+
+            self.byte_index += cluster.len();
+            self.char_index = self.string_index;
+            self.string_index += cluster.chars().count();
+
+            if let Some(split) = split_cluster(cluster) {
+                return Some(split)
             }
         }
-        
-        None
     }
 
     fn next_pixel(&mut self, default: PixelData) -> Option<PixelData> {
-        if let Some(c) = self.next() {
-            let color = if let Some(color) = self.entries[self.cur_entry].get_color(self.string_index - 1) {
+        if let Some((c, marks)) = self.next() {
+            let color = if let Some(color) = self.entries[self.cur_entry].get_color(self.char_index) {
                 color
             }
             else {
                 default.get_color_set()
             };
 
-            return Some(PixelData::new_color_set(
-                c,
-                color
-            ))
+            return Some(PixelData { marks, ..PixelData::new_color_set(c, color) })
         }
 
         None
     }
+}
 
-    fn more_chars(&self) -> bool {
-        if let Some(entry) = self.entries.get(self.cur_entry) {
-            if self.string_index < entry.len {
-                return true
-            }
+///A character laid out on a line.
+#[derive(Copy, Clone)]
+struct Glyph {
+    data:  PixelData,
+    width: i32,
+}
+
+impl Glyph {
+    fn new(data: PixelData) -> Self {
+        Glyph { data, width: data.width() }
+    }
+
+    fn is_space(&self) -> bool {
+        self.data.character == ' '
+    }
+}
+
+fn glyphs_width(glyphs: &[Glyph]) -> i32 {
+    glyphs.iter().map(|g| g.width).sum()
+}
+
+struct Line {
+    glyphs:  Vec<Glyph>,
+    ///the line was broken by wrapping rather than a new line or the end of the entry.
+    wrapped: bool,
+}
+
+///Breaks the paragraphs of an entry into lines, the first line has a different width from the others for the indent.
+struct LineBreaker {
+    first: i32,
+    other: i32,
+    lines: Vec<Line>,
+    cur:   Vec<Glyph>,
+    used:  i32,
+}
+
+impl LineBreaker {
+    fn new(first: i32, other: i32) -> Self {
+        LineBreaker {
+            first,
+            other,
+            lines: Vec::new(),
+            cur:   Vec::new(),
+            used:  0,
         }
+    }
 
-        false
+    fn avail(&self) -> i32 {
+        if self.lines.is_empty() { self.first }
+                            else { self.other }
     }
 
-    fn cur_entry(&mut self) -> Option<&Entry> {
-        self.entries.get(self.cur_entry)
+    fn room(&self) -> i32 {
+        self.avail() - self.used
     }
 
-    fn next_entry(&mut self) {
-        self.cur_entry += 1;
-        self.byte_index = 0;
-        self.string_index = 0;
+    fn push(&mut self, glyph: Glyph) {
+        self.used += glyph.width;
+        self.cur.push(glyph);
+    }
+
+    fn finish(&mut self, wrapped: bool) {
+        self.lines.push(Line { glyphs: std::mem::take(&mut self.cur), wrapped });
+        self.used = 0;
+    }
+
+    ///Wraps between words, the spaces the line was broken at aren't shown.
+    fn break_line(&mut self) {
+        while self.cur.last().is_some_and(|g| g.is_space()) {
+            self.cur.pop();
+        }
+
+        self.finish(true);
+    }
+
+    ///Adds a glyph, moving to the next line if it doesn't fit on this one.
+    fn push_char(&mut self, mut glyph: Glyph) {
+        if glyph.width > self.room() && !self.cur.is_empty() {
+            self.finish(true);
+        }
+
+        //a wide character that would never fit.
+        if glyph.width > self.avail() {
+            glyph = Glyph { data: glyph.data.with_char(' '), width: 1 };
+        }
+
+        self.push(glyph);
+    }
+
+    ///Puts as much of the word as it can on this line followed by a hyphen, leaving at least two characters on either side.
+    ///Returns how many glyphs were used.
+    fn hyphenate(&mut self, word: &[Glyph]) -> usize {
+        let mut width = 1;
+        let mut split = 0;
+
+        for glyph in word {
+            if width + glyph.width > self.room() { break }
+
+            width += glyph.width;
+            split += 1;
+        }
+
+        if split < 2 || word.len() - split < 2 { return 0 }
+
+        for glyph in &word[..split] {
+            self.push(*glyph);
+        }
+        self.push(Glyph::new(word[split - 1].data.with_char('-')));
+        self.finish(true);
+
+        split
+    }
+
+    fn add_chars(&mut self, paragraph: &[Glyph]) {
+        for glyph in paragraph {
+            self.push_char(*glyph);
+        }
+    }
+
+    fn add_words(&mut self, paragraph: &[Glyph], hyphenate: bool) {
+        let mut start = 0;
+
+        while start < paragraph.len() {
+            let space = paragraph[start].is_space();
+            let len = paragraph[start..].iter().take_while(|g| g.is_space() == space).count();
+            let run = &paragraph[start..start + len];
+            start += len;
+
+            if space {
+                //spaces at the start of a wrapped line are dropped, but kept at the start of a paragraph.
+                let after_break = self.cur.is_empty() && self.lines.last().is_some_and(|line| line.wrapped);
+
+                if after_break { continue }
+
+                if glyphs_width(run) <= self.room() {
+                    run.iter().for_each(|g| self.push(*g));
+                }
+                else {
+                    self.break_line();
+                }
+
+                continue
+            }
+
+            let mut word = run;
+            while !word.is_empty() {
+                if glyphs_width(word) <= self.room() {
+                    word.iter().for_each(|g| self.push(*g));
+                    break
+                }
+
+                if hyphenate {
+                    let used = self.hyphenate(word);
+                    if used > 0 {
+                        word = &word[used..];
+                        continue
+                    }
+                }
+
+                if !self.cur.is_empty() {
+                    self.break_line();
+                    continue
+                }
+
+                //the word is longer than a whole line.
+                let fits = word.iter().scan(0, |width, g| { *width += g.width; Some(*width) }).take_while(|width| *width <= self.room()).count();
+                let fits = max(fits, 1);
+
+                word[..fits].iter().for_each(|g| self.push_char(*g));
+                word = &word[fits..];
+            }
+        }
+    }
+
+    fn add_cut(&mut self, paragraph: &[Glyph], ellipsis: bool) {
+        for (i, glyph) in paragraph.iter().enumerate() {
+            if glyph.width <= self.room() {
+                self.push(*glyph);
+                continue
+            }
+
+            if ellipsis {
+                let data = paragraph[i.saturating_sub(1)].data;
+
+                while self.room() < 1 {
+                    let glyph = self.cur.pop().unwrap();
+                    self.used -= glyph.width;
+                }
+
+                self.push(Glyph::new(data.with_char('…')));
+            }
+
+            break
+        }
     }
 }
 
-struct TabData {
-    pub fg:    Color,
-    pub bg:    Color,
-    pub count: usize,
+///Everything besides the text that changes how many lines an entry wraps to.
+#[derive(Copy, Clone, PartialEq)]
+struct WrapKey {
+    first:   i32,
+    other:   i32,
+    tab_len: usize,
+    wrap:    Wrap,
 }
 
+///Lays out entries into rows of pixels exactly as wide as the text.
 struct EntryIter<'a> {
-    char_iter:  CharIter<'a>,
-    entry_line: usize,
+    entries:    &'a VecDeque<Entry>,
+    next_entry: usize,
     default:    PixelData,
-    next_pos:   Coord,
     width:      i32,
-    indent: &'a Indent,
-    new_line:   bool,
+    indent:     &'a Indent,
     tab_len:    usize,
-    cur_tab:    Option<TabData>,
+    wrap:       Wrap,
+    rows:       VecDeque<Vec<Pixel>>,
+    row:        std::vec::IntoIter<Pixel>,
 }
 
 impl<'a> EntryIter<'a> {
-    fn new(entries: &'a VecDeque<Entry>, default: PixelData, offset: Coord, size: Coord, indent: &'a Indent, tab_len: usize) -> EntryIter<'a> {
+    ///Starts at the entry offset.y, skipping offset.x lines.
+    ///Only the entry the first line shown is in gets laid out to find it, the ones before are skipped by their height.
+    fn new(entries: &'a VecDeque<Entry>, default: PixelData, offset: Coord, size: Coord, indent: &'a Indent, tab_len: usize, wrap: Wrap) -> EntryIter<'a> {
         let mut entry_iter = EntryIter {
-            char_iter:    CharIter::new(entries, offset.y as usize),
-            entry_line:   0,
+            entries,
+            next_entry: offset.y as usize,
             default,
-            next_pos:     Coord{x: 0, y: 0},
-            width:        size.x,
+            width:      size.x,
             indent,
-            new_line:     false,
             tab_len,
-            cur_tab:      None,
+            wrap,
+            rows:       VecDeque::new(),
+            row:        Vec::new().into_iter(),
         };
 
-        //entry_iter.skip(offset.x as usize);
-        entry_iter.go_to(Coord{y: offset.x, x: 0});
-        entry_iter.next_pos = Coord{x: 0, y: 0}; //reset current position because it is changed in go_to.
+        let mut skip = offset.x as usize;
+
+        while entry_iter.width > 0 && entry_iter.next_entry < entries.len() {
+            let height = entry_iter.height(entry_iter.next_entry);
+            if skip < height { break }
+
+            skip -= height;
+            entry_iter.next_entry += 1;
+        }
+
+        while skip > 0 && entry_iter.load_entry() {
+            let skipped = min(skip, entry_iter.rows.len());
+            entry_iter.rows.drain(..skipped);
+            skip -= skipped;
+        }
 
         entry_iter
     }
 
+    ///Number of lines the entry at index takes up, only wrapped again if it or the layout changed since the last time.
+    fn height(&self, index: usize) -> usize {
+        let key = self.wrap_key();
 
-    // fn skip(&mut self, mut skip: usize) {
-    //     while skip > 0 {
-    //         if let Some(entry) = self.char_iter.cur_entry() {
-    //             let height = entry.height(self.width as usize, &self.indent, self.tab_len);
-
-    //             if height <= skip {
-    //                 skip -= height;
-    //                 self.char_iter.cur_entry += 1;
-    //             }
-    //             else {
-    //                 //skipping into somewhere inside an entry.
-    //                 self.go_to(Coord{y: skip as i32, x: 0});
-    //                 self.next_pos = Coord{x: 0, y: 0}; //reset current position because it is changed in go_to.
-    //                 return
-    //             }
-    //         }
-    //         else {
-    //             //skipped all of the entries.
-    //             return
-    //         }
-    //     }
-    // }
+        match self.entries[index].height.get() {
+            Some((k, height)) if k == key => height,
+            _ => self.lines(index).len(),
+        }
+    }
 
-    fn inc_next_pos(&mut self) {
-        self.next_pos.x += 1;
+    fn wrap_key(&self) -> WrapKey {
+        WrapKey {
+            first:   self.width - self.indent(0),
+            other:   self.width - self.indent(1),
+            tab_len: self.tab_len,
+            wrap:    self.wrap,
+        }
+    }
 
-        if self.next_pos.x >= self.width {
-            self.next_pos.x  = 0;
-            self.next_pos.y += 1;
+    fn load_entry(&mut self) -> bool {
+        if self.width <= 0 || self.next_entry >= self.entries.len() { return false }
 
-            //record what line of the current entry we are on for indents.
-            self.entry_line += 1;
+        let lines = self.lines(self.next_entry);
+        let align = self.entries[self.next_entry].align;
+        let pad = self.pad(self.next_entry);
 
-            if self.new_line {
-                self.new_line = false;
-            }
-            else {
-                //move to the next entry
-                if !self.char_iter.more_chars() {
-                    self.char_iter.next_entry();
-                    self.entry_line = 0;
-                }
-            }
+        self.rows = lines.iter().enumerate().map(|(i, line)| self.row(line, i, align, pad)).collect();
+        self.next_entry += 1;
+        true
+    }
+
+    ///What empty cells of the entry are filled with.
+    fn pad(&self, index: usize) -> PixelData {
+        let mut data = self.default;
+
+        if let Some(colors) = self.entries[index].colors {
+            data.set_color_set(colors);
         }
+
+        data
     }
 
-    fn next_pixel(&mut self) -> Option<PixelData> {
-        //handling in progress tabs.
-        if let Some(tab) = self.cur_tab.as_mut() {
-            let pix = PixelData {
-                character: ' ',
-                fg: tab.fg,
-                bg: tab.bg,
-            };
+    ///Width of the indent of a line of an entry, it always leaves room for at least one character.
+    fn indent(&self, line: usize) -> i32 {
+        let indent = if line == 0 { self.indent.normal() }
+                              else { self.indent.hanging() };
+
+        min(indent as i32, self.width - 1)
+    }
 
-            tab.count -= 1;
+    ///The entry split up at new lines, with tabs turned into spaces.
+    fn paragraphs(&self, index: usize) -> Vec<Vec<Glyph>> {
+        let mut chars = CharIter::new(self.entries, index);
+        let mut paragraphs = vec![Vec::new()];
 
-            if tab.count == 0 {
-                self.cur_tab = None;
+        while let Some(d) = chars.next_pixel(self.default) {
+            if d.character == '\n' {
+                paragraphs.push(Vec::new());
+                continue
             }
 
-            return Some(pix)
-        }
+            let paragraph = paragraphs.last_mut().unwrap();
 
-        //try to get next pixel from entry.
-        loop {
-            if let Some(mut d) = self.char_iter.next_pixel(self.default) {
-                match d.character {
-                    '\n' => {
-                        self.new_line = true;
-                        return None
-                    }
-                    '\t' => {
-                        match self.tab_len {
-                            0 => {}
-                            1 => {
-                                d.character = ' ';
-                                return Some(d)
-                            }
-                            _ => {
-                                self.cur_tab = Some(
-                                    TabData {
-                                        fg: d.fg,
-                                        bg: d.bg,
-                                        count: self.tab_len - 1,
-                                    }
-                                );
-    
-                                d.character = ' ';
-                                return Some(d)
-                            }
-                        }
-                    }
-                    _ => {
-                        return Some(d)
-                    }
+            if d.character == '\t' {
+                for _ in 0..self.tab_len {
+                    paragraph.push(Glyph::new(d.with_char(' ')));
                 }
             }
             else {
-                return None
+                paragraph.push(Glyph::new(d));
             }
         }
-    }
 
-    fn next(&mut self) -> Option<Pixel> {
-        let mut data = self.default;
+        paragraphs
+    }
 
-        if let Some(entry) = self.char_iter.cur_entry() {
-            if let Some(colors) = entry.colors {
-                data.fg = colors.fg;
-                data.fg = colors.bg;
+    ///Wraps the entry at index, remembering how many lines it took for height.
+    fn lines(&self, index: usize) -> Vec<Line> {
+        let key = self.wrap_key();
+        let mut breaker = LineBreaker::new(key.first, key.other);
+
+        for paragraph in self.paragraphs(index) {
+            match self.wrap {
+                Wrap::Char      => breaker.add_chars(&paragraph),
+                Wrap::Word      => breaker.add_words(&paragraph, false),
+                Wrap::Hyphenate => breaker.add_words(&paragraph, true),
+                Wrap::Truncate  => breaker.add_cut(&paragraph, false),
+                Wrap::Ellipsis  => breaker.add_cut(&paragraph, true),
             }
-        }
-        else {
-            self.inc_next_pos();
-            return None
+
+            breaker.finish(false);
         }
 
-        //deciding wether to pull data from the entry,
-        //handling new line
-        if !self.new_line
-        //first line indent
-        && !((self.entry_line == 0) && (self.next_pos.x < self.indent.normal() as i32))
-        //other line indent
-        && !((self.entry_line != 0) && (self.next_pos.x < self.indent.hanging() as i32)) {
+        self.entries[index].height.set(Some((key, breaker.lines.len())));
+        breaker.lines
+    }
 
-            if let Some(d) = self.next_pixel() {
-                data = d;
-            }
-        }
+    fn row(&self, line: &Line, index: usize, align: Align, pad: PixelData) -> Vec<Pixel> {
+        let indent = self.indent(index);
+        let extra = max(self.width - indent - glyphs_width(&line.glyphs), 0);
 
-        self.inc_next_pos();
+        //the first glyph of each gap between words.
+        let gaps: Vec<usize> = match align {
+            Align::Justify if line.wrapped => (1..line.glyphs.len())
+                .filter(|i| line.glyphs[*i].is_space() && !line.glyphs[i - 1].is_space())
+                .collect(),
+            _ => Vec::new(),
+        };
 
-        Some(Pixel::Opaque(data))
-    }
+        let left = match align {
+            Align::Left | Align::Justify => 0,
+            Align::Center                => extra / 2,
+            Align::Right                 => extra,
+        };
+
+        let mut row = vec![Pixel::Opaque(pad); (indent + left) as usize];
+
+        for (i, glyph) in line.glyphs.iter().enumerate() {
+            row.push(Pixel::Opaque(glyph.data));
 
-    fn go_to(&mut self, pos: Coord) {
-        while self.next_pos != pos {
-            self.next();
+            if glyph.width == 2 {
+                row.push(Pixel::Continuation);
+            }
+
+            if let Some(gap) = gaps.iter().position(|g| *g == i) {
+                let spaces = extra / gaps.len() as i32 + (gap < (extra as usize % gaps.len())) as i32;
+
+                for _ in 0..spaces {
+                    row.push(Pixel::Opaque(glyph.data));
+                }
+            }
         }
+
+        row.resize(self.width as usize, Pixel::Opaque(pad));
+        row
     }
+}
 
-    pub fn get(&mut self, pos: Coord) -> Option<Pixel> {
-        self.go_to(pos);
+impl<'a> Iterator for EntryIter<'a> {
+    type Item = Pixel;
+
+    fn next(&mut self) -> Option<Pixel> {
+        loop {
+            if let Some(pixel) = self.row.next() {
+                return Some(pixel)
+            }
 
-        self.next()
+            match self.rows.pop_front() {
+                Some(row) => self.row = row.into_iter(),
+                None => if !self.load_entry() { return None },
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    //use crate::test_helpers::*;
+    use crate::test_helpers::*;
+    use crate::ColorSlice;
+    use crate::frame_types::layout::{ILayout, Object};
+    use crate::modifiers::position;
+    use crate::input::Router;
+    use crossterm::event::{KeyEvent, KeyModifiers};
 
     #[test]
     fn blank_test() {
@@ -893,4 +1339,278 @@ mod tests {
             assert_eq!(buf.buffer.get_flat(i), *x)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn colors_snapshot() {
+        let mut buf = ScreenBuf::new(Coord{x: 10, y: 6});
+
+        let text = new();
+
+        {
+            let mut temp = text.borrow_mut();
+
+            temp.indent = Indent::Hanging(2);
+
+            temp.entries.push_back(Entry::new_color("warning:\tdisk almost full", ColorSet::new(Color::Yellow, Color::Black).with(Attribute::Bold)));
+            temp.entries.push_back(Entry::new(ColorString {
+                string: "ok fail ok".to_string(),
+                colors: vec![ColorSlice { start: 3, end: 7, colors: ColorSet::new(Color::Red, Color::Black).with(Attribute::Underlined) }],
+            }));
+        }
+
+        text.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 10, y: 6});
+
+        assert_snapshot("text_colors", &buf.buffer);
+    }
+
+    #[test]
+    fn wide_and_combining() {
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 4});
+
+        let text = new();
+
+        {
+            let mut temp = text.borrow_mut();
+
+            //the last wide character does not fit on the first line so it moves down.
+            temp.entries.push_back(Entry::new("a世界b\u{200b}c"));
+            //combining marks share the cell of the character before them.
+            temp.entries.push_back(Entry::new("ce\u{301}g\u{308}\r\nx"));
+        }
+
+        text.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 4, y: 4});
+
+        assert_chars(&buf.buffer, "
+a世 
+界bc
+cég\u{308} 
+x   ");
+    }
+
+    #[test]
+    fn wide_colors() {
+        let red = ColorSet::new(Color::Red, Color::Black);
+
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 1});
+
+        let text = new();
+
+        text.borrow_mut().entries.push_back(Entry::new(ColorString {
+            string: "e\u{301}世x".to_string(),
+            colors: vec![ColorSlice { start: 2, end: 3, colors: red }],
+        }));
+
+        text.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 4, y: 1});
+
+        assert_snapshot("text_wide_colors", &buf.buffer);
+    }
+
+    fn draw(text: &Text, size: Coord) -> ScreenBuf {
+        let mut buf = ScreenBuf::new(size);
+        text.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+        buf
+    }
+
+    #[test]
+    fn scrolling() {
+        let text = new();
+
+        for entry in ["aaaa", "bbbb", "cccc", "dd\nee"] {
+            text.borrow_mut().entries.push_back(Entry::new(entry));
+        }
+
+        //nothing is known about the size until it is drawn, so scrolling is only limited then.
+        assert_eq!(text.borrow().total_height(), 0);
+        text.borrow_mut().scroll_to(1);
+        assert_eq!(text.borrow().scroll_pos(), 1);
+
+        assert_chars(&draw(&text, Coord{x: 4, y: 2}).buffer, "
+bbbb
+cccc");
+        assert_eq!(text.borrow().total_height(), 5);
+        assert_eq!(text.borrow().height_at(3), 8);
+        assert_eq!(text.borrow().max_scroll(), 3);
+
+        text.borrow_mut().scroll_by(-1);
+        assert_chars(&draw(&text, Coord{x: 4, y: 2}).buffer, "
+aaaa
+bbbb");
+
+        text.borrow_mut().page_down();
+        text.borrow_mut().page_down();
+        assert_eq!(text.borrow().scroll_pos(), 3);
+        assert_chars(&draw(&text, Coord{x: 4, y: 2}).buffer, "
+dd  
+ee  ");
+
+        text.borrow_mut().home();
+        text.borrow_mut().page_up();
+        assert_eq!(text.borrow().scroll_pos(), 0);
+
+        //following only sticks once the end is reached.
+        text.borrow_mut().follow = true;
+        text.borrow_mut().entries.push_back(Entry::new("ff"));
+        assert_eq!(text.borrow().scroll_pos(), 0);
+
+        text.borrow_mut().end();
+        text.borrow_mut().entries.push_back(Entry::new("gg"));
+        assert_chars(&draw(&text, Coord{x: 4, y: 2}).buffer, "
+ff  
+gg  ");
+
+        //keys scroll it through a router.
+        let mut layout = ILayout::new();
+        let id = layout.add(Object::new(text.clone(), position::new()));
+
+        let mut router = Router::new();
+        router.register(id, text.clone());
+        router.set_focus(Some(id));
+
+        let key = Input::KeyBoard(KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE));
+        assert_eq!(router.handle(&key, &layout, None), EventResult::Consumed);
+        assert_eq!(text.borrow().scroll_pos(), 3);
+
+        text.borrow_mut().entries.push_back(Entry::new("hh"));
+        assert_eq!(text.borrow().scroll_pos(), 3);
+    }
+
+    #[test]
+    fn remembered_heights() {
+        let text = new();
+        text.borrow_mut().entries.push_back(Entry::new("aaaa"));
+        text.borrow_mut().entries.push_back(Entry::new("bbbb\ncc"));
+
+        assert_eq!(text.borrow().height_at(4), 3);
+        assert_eq!(text.borrow().height_at(2), 5);
+
+        //changing the text or anything about the wrapping forgets the old height.
+        text.borrow_mut().entries[0].set_text("aaaaaaaa");
+        assert_eq!(text.borrow().height_at(4), 4);
+
+        text.borrow_mut().indent = Indent::Normal(2);
+        assert_eq!(text.borrow().height_at(4), 6);
+
+        text.borrow_mut().scroll_to(3);
+        assert_chars(&draw(&text, Coord{x: 4, y: 3}).buffer, "
+  bb
+bb  
+cc  ");
+    }
+
+    #[test]
+    fn scrollbar() {
+        let text = new();
+        text.borrow_mut().scrollbar = Some(Scrollbar::default());
+
+        for entry in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            text.borrow_mut().entries.push_back(Entry::new(entry));
+        }
+
+        assert_chars(&draw(&text, Coord{x: 3, y: 4}).buffer, "
+a █
+b █
+c │
+d │");
+
+        text.borrow_mut().end();
+        assert_chars(&draw(&text, Coord{x: 3, y: 4}).buffer, "
+e │
+f │
+g █
+h █");
+
+        //everything fits so the thumb fills the track.
+        text.borrow_mut().entries.truncate(2);
+        assert_chars(&draw(&text, Coord{x: 3, y: 4}).buffer, "
+a █
+b █
+  █
+  █");
+    }
+
+    #[test]
+    fn wrap_modes() {
+        let expected = [
+            (Wrap::Char, "
+a wonder
+ful day 
+        "),
+            (Wrap::Word, "
+a       
+wonderfu
+l day   "),
+            (Wrap::Hyphenate, "
+a wonde-
+rful day
+        "),
+            (Wrap::Truncate, "
+a wonder
+        
+        "),
+            (Wrap::Ellipsis, "
+a wonde…
+x       
+        "),
+        ];
+
+        for (wrap, screen) in expected {
+            let text = new();
+
+            {
+                let mut temp = text.borrow_mut();
+
+                temp.wrap = wrap;
+                temp.entries.push_back(Entry::new("a wonderful day"));
+
+                if wrap == Wrap::Ellipsis {
+                    temp.entries.push_back(Entry::new("x"));
+                }
+            }
+
+            assert_chars(&draw(&text, Coord{x: 8, y: 3}).buffer, screen);
+        }
+    }
+
+    #[test]
+    fn align() {
+        let text = new();
+
+        {
+            let mut temp = text.borrow_mut();
+
+            temp.wrap = Wrap::Word;
+            temp.indent = Indent::Hanging(1);
+
+            temp.entries.push_back(Entry::new("ab cd efg hi").aligned(Align::Center));
+            temp.entries.push_back(Entry::new("ab cd efg\thi").aligned(Align::Right));
+            temp.entries.push_back(Entry::new("a b c dddd").aligned(Align::Justify));
+        }
+
+        text.borrow_mut().tab_spaces = 1;
+
+        assert_chars(&draw(&text, Coord{x: 8, y: 6}).buffer, "
+ ab cd  
+ efg hi 
+   ab cd
+  efg hi
+a   b  c
+ dddd   ");
+    }
+
+    #[test]
+    fn clusters() {
+        assert_eq!(split_cluster("e\u{301}"), Some(('é', Marks::NONE)));
+        assert_eq!(split_cluster("e\u{302}\u{301}"), Some(('ế', Marks::NONE)));
+        assert_eq!(split_cluster("\u{301}"), None);
+
+        //there is no single character for these so the rest is kept as marks.
+        assert_eq!(split_cluster("e\u{301}\u{302}"), Some(('é', Marks::new("\u{302}"))));
+        assert_eq!(split_cluster("x\u{301}"), Some(('x', Marks::new("\u{301}"))));
+        assert_eq!(split_cluster("👨\u{200d}👩\u{200d}👧"), Some(('👨', Marks::new("\u{200d}👩\u{200d}👧"))));
+
+        let text = new();
+        text.borrow_mut().entries.push_back(Entry::new("x\u{301}y👨\u{200d}👩\u{200d}👧\u{e01}\u{e35}"));
+
+        assert_chars(&draw(&text, Coord{x: 6, y: 1}).buffer, "x\u{301}y👨\u{200d}👩\u{200d}👧\u{e01}\u{e35} ");
+    }
+}
@@ -0,0 +1,283 @@
+use crate::prelude::*;
+use crate::input::{EventHandler, Event, EventResult};
+use super::editor::{Editor, Look, placeholder_row, scroll_to_show};
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use std::cell::Cell;
+use std::ops::Range;
+
+pub type TextArea = Rc<RefCell<ITextArea>>;
+
+pub fn new() -> TextArea {
+    wrap(ITextArea::new())
+}
+
+/// Editable text over many lines, which scrolls both ways to keep the cursor in view instead of wrapping.
+/// It takes the same keys as TextInput, along with enter for a new line,
+/// up and down to move between lines, and control home and end to go to the start and end of all of the text.
+/// Typing is undone a word at a time, other edits one at a time.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - text
+/// - set_text
+/// - lines
+/// - cursor
+/// - set_cursor
+/// - selection
+/// - selected_text
+/// - select_all
+/// - insert
+/// - undo
+/// - redo
+/// - focused
+pub struct ITextArea {
+    pub default:            PixelData,
+    ///Colors of selected text.
+    pub selected:           ColorSet,
+    ///Shown when there is no text.
+    pub placeholder:        String,
+    pub placeholder_colors: ColorSet,
+    editor:                 Editor,
+    focused:                bool,
+    ///first column and line shown.
+    scroll:                 Cell<Coord>,
+}
+
+impl IFrame for ITextArea {
+    fn get_draw_data(&self, screenbuf: &mut ScreenBuf, _offset: Coord, size: Coord) {
+        let look = self.look();
+        let starts = self.line_starts();
+
+        let line = starts.partition_point(|start| *start <= self.editor.cursor) - 1;
+        let column = self.editor.column(self.editor.cursor, None);
+
+        let old = self.scroll.get();
+        let scroll = Coord {
+            x: scroll_to_show(old.x as usize, column, size.x) as i32,
+            y: scroll_to_show(old.y as usize, line, size.y) as i32,
+        };
+        self.scroll.set(scroll);
+
+        let rows: Vec<Vec<Pixel>> = if self.editor.text.is_empty() {
+            vec![placeholder_row(&self.placeholder, self.placeholder_colors, &look, size.x)]
+        }
+        else {
+            starts.iter()
+                .skip(scroll.y as usize)
+                .take(size.y.max(0) as usize)
+                .map(|start| self.editor.row(*start, scroll.x as usize, size.x, &look))
+                .collect()
+        };
+
+        let start = screenbuf.offset();
+
+        for pos in screenbuf.draw_to() {
+            let local = pos - start;
+
+            let pixel = match rows.get(local.y as usize).and_then(|row| row.get(local.x as usize)) {
+                Some(pixel) => *pixel,
+                None => Pixel::Opaque(self.default),
+            };
+
+            screenbuf.set(pos, pixel);
+        }
+    }
+}
+
+impl ITextArea {
+    pub fn new() -> Self {
+        ITextArea {
+            default:            PixelData::new(
+                ' ',
+                Color::Rgb{r: 255, g: 255, b: 255},
+                Color::Rgb{r:   0, g:   0, b:   0},
+            ),
+            selected:           ColorSet::new(Color::Rgb{r: 0, g: 0, b: 0}, Color::Rgb{r: 200, g: 200, b: 200}),
+            placeholder:        String::new(),
+            placeholder_colors: ColorSet::new(Color::Rgb{r: 128, g: 128, b: 128}, Color::Rgb{r: 0, g: 0, b: 0}),
+            editor:             Editor::new(true),
+            focused:            false,
+            scroll:             Cell::new(Coord{x: 0, y: 0}),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.editor.text
+    }
+
+    ///Replaces the text, putting the cursor at the end. This can be undone.
+    pub fn set_text(&mut self, text: &str) {
+        self.editor.set_text(text);
+    }
+
+    pub fn lines(&self) -> std::str::Split<'_, char> {
+        self.editor.text.split('\n')
+    }
+
+    ///Byte index of the cursor in the text.
+    pub fn cursor(&self) -> usize {
+        self.editor.cursor
+    }
+
+    ///Moves the cursor to the start of the character at the byte index, clearing the selection.
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.editor.set_cursor(pos);
+    }
+
+    ///Byte range of the selected text.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.editor.selection()
+    }
+
+    pub fn selected_text(&self) -> &str {
+        match self.editor.selection() {
+            Some(range) => &self.editor.text[range],
+            None => "",
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.editor.select_all();
+    }
+
+    ///Replaces the selection with the text, or puts it at the cursor.
+    pub fn insert(&mut self, text: &str) {
+        self.editor.insert(text);
+    }
+
+    ///Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.editor.undo()
+    }
+
+    ///Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.editor.redo()
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    ///Byte index of the start of each line.
+    fn line_starts(&self) -> Vec<usize> {
+        std::iter::once(0)
+            .chain(self.editor.text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect()
+    }
+
+    fn look(&self) -> Look {
+        Look {
+            default:  self.default,
+            selected: self.selected,
+            mask:     None,
+            focused:  self.focused,
+        }
+    }
+}
+
+impl Default for ITextArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHandler for ITextArea {
+    fn handle_event(&mut self, event: &Event) -> EventResult {
+        let used = match event.input {
+            Input::KeyBoard(key) => self.editor.handle_key(key),
+            Input::Paste(text) => {
+                self.editor.insert(text);
+                true
+            }
+            Input::Mouse(e) => match (e.kind, event.local) {
+                (MouseEventKind::Down(MouseButton::Left), Some(local)) => {
+                    let scroll = self.scroll.get();
+                    let starts = self.line_starts();
+
+                    let line = ((scroll.y + local.y.max(0)) as usize).min(starts.len() - 1);
+                    let column = (scroll.x + local.x.max(0)) as usize;
+
+                    self.editor.set_cursor(self.editor.at_column(starts[line], column, None));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if used { EventResult::Consumed }
+           else { EventResult::Ignored }
+    }
+
+    fn focus_changed(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::layout::{ILayout, Object};
+    use crate::modifiers::position;
+    use crate::test_helpers::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+
+    fn draw(area: &TextArea, size: Coord) -> ScreenBuf {
+        let mut buf = ScreenBuf::new(size);
+        area.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+        buf
+    }
+
+    #[test]
+    fn editing_lines() {
+        let area = new();
+        area.borrow_mut().placeholder = "notes".to_string();
+        assert_chars(&draw(&area, Coord{x: 4, y: 2}).buffer, "
+note
+    ");
+
+        let mut layout = ILayout::new();
+        let id = layout.add(Object::new(area.clone(), position::new()));
+
+        let send = |input: Input, local: Option<Coord>| {
+            area.borrow_mut().handle_event(&Event { input: &input, target: id, local })
+        };
+        let key = |code: KeyCode, modifiers: KeyModifiers| Input::KeyBoard(KeyEvent::new(code, modifiers));
+
+        send(Input::Paste("one\r\ntwo\nthree".to_string()), None);
+        assert_eq!(area.borrow().lines().collect::<Vec<_>>(), vec!["one", "two", "three"]);
+
+        //the view follows the cursor down and to the right.
+        assert_chars(&draw(&area, Coord{x: 4, y: 2}).buffer, "
+o   
+ree ");
+
+        send(key(KeyCode::Home, KeyModifiers::CONTROL), None);
+        send(key(KeyCode::Down, KeyModifiers::SHIFT), None);
+        assert_eq!(area.borrow().selected_text(), "one\n");
+        assert_chars(&draw(&area, Coord{x: 4, y: 2}).buffer, "
+two 
+thre");
+
+        assert_eq!(send(key(KeyCode::Enter, KeyModifiers::NONE), None), EventResult::Consumed);
+        assert_eq!(area.borrow().text(), "\ntwo\nthree");
+
+        let click = Input::Mouse(MouseEvent {
+            kind:      MouseEventKind::Down(MouseButton::Left),
+            column:    0,
+            row:       0,
+            modifiers: KeyModifiers::NONE,
+        });
+        send(click, Some(Coord{x: 2, y: 1}));
+        assert_eq!(area.borrow().cursor(), 7);
+
+        send(key(KeyCode::Char('z'), KeyModifiers::CONTROL), None);
+        assert_eq!(area.borrow().text(), "one\ntwo\nthree");
+        send(key(KeyCode::Char('z'), KeyModifiers::CONTROL | KeyModifiers::SHIFT), None);
+        assert_eq!(area.borrow().text(), "\ntwo\nthree");
+    }
+}
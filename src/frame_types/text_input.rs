@@ -0,0 +1,291 @@
+use crate::prelude::*;
+use crate::input::{EventHandler, Event, EventResult};
+use super::editor::{Editor, Look, placeholder_row, scroll_to_show};
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use std::cell::Cell;
+use std::ops::Range;
+
+pub type TextInput = Rc<RefCell<ITextInput>>;
+
+pub fn new() -> TextInput {
+    wrap(ITextInput::new())
+}
+
+/// A single line of editable text, drawn on the first row. It scrolls sideways to keep the cursor in view.
+/// Keys, pastes and clicks come in through its EventHandler, and the cursor is only shown while it is focused.
+/// Enter, tab and keys with nothing to do are ignored so they can bubble up to the object's parents.
+/// - Left and right move by a character, or by a word with control.
+/// - Home and end go to the start and end.
+/// - Holding shift while moving selects.
+/// - Backspace and delete remove a character, or a word with control.
+/// - Control a selects everything, control z undoes and control y redoes.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - text
+/// - set_text
+/// - cursor
+/// - set_cursor
+/// - selection
+/// - selected_text
+/// - select_all
+/// - insert
+/// - undo
+/// - redo
+/// - focused
+pub struct ITextInput {
+    pub default:            PixelData,
+    ///Colors of selected text.
+    pub selected:           ColorSet,
+    ///Shown when there is no text.
+    pub placeholder:        String,
+    pub placeholder_colors: ColorSet,
+    ///Every character is drawn as this, for passwords. Word jumps go to the start and end while it is set.
+    pub mask:               Option<char>,
+    editor:                 Editor,
+    focused:                bool,
+    ///first column shown.
+    scroll:                 Cell<usize>,
+}
+
+impl IFrame for ITextInput {
+    fn get_draw_data(&self, screenbuf: &mut ScreenBuf, _offset: Coord, size: Coord) {
+        let look = self.look();
+
+        let column = self.editor.column(self.editor.cursor, self.mask);
+        let scroll = scroll_to_show(self.scroll.get(), column, size.x);
+        self.scroll.set(scroll);
+
+        let row = if self.editor.text.is_empty() { placeholder_row(&self.placeholder, self.placeholder_colors, &look, size.x) }
+                                              else { self.editor.row(0, scroll, size.x, &look) };
+
+        let start = screenbuf.offset();
+
+        for pos in screenbuf.draw_to() {
+            let local = pos - start;
+
+            let pixel = match row.get(local.x as usize) {
+                Some(pixel) if local.y == 0 => *pixel,
+                _ => Pixel::Opaque(self.default),
+            };
+
+            screenbuf.set(pos, pixel);
+        }
+    }
+}
+
+impl ITextInput {
+    pub fn new() -> Self {
+        ITextInput {
+            default:            PixelData::new(
+                ' ',
+                Color::Rgb{r: 255, g: 255, b: 255},
+                Color::Rgb{r:   0, g:   0, b:   0},
+            ),
+            selected:           ColorSet::new(Color::Rgb{r: 0, g: 0, b: 0}, Color::Rgb{r: 200, g: 200, b: 200}),
+            placeholder:        String::new(),
+            placeholder_colors: ColorSet::new(Color::Rgb{r: 128, g: 128, b: 128}, Color::Rgb{r: 0, g: 0, b: 0}),
+            mask:               None,
+            editor:             Editor::new(false),
+            focused:            false,
+            scroll:             Cell::new(0),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.editor.text
+    }
+
+    ///Replaces the text, putting the cursor at the end. New lines are turned into spaces.
+    pub fn set_text(&mut self, text: &str) {
+        self.editor.set_text(text);
+    }
+
+    ///Byte index of the cursor in the text.
+    pub fn cursor(&self) -> usize {
+        self.editor.cursor
+    }
+
+    ///Moves the cursor to the start of the character at the byte index, clearing the selection.
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.editor.set_cursor(pos);
+    }
+
+    ///Byte range of the selected text.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.editor.selection()
+    }
+
+    pub fn selected_text(&self) -> &str {
+        match self.editor.selection() {
+            Some(range) => &self.editor.text[range],
+            None => "",
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.editor.select_all();
+    }
+
+    ///Replaces the selection with the text, or puts it at the cursor.
+    pub fn insert(&mut self, text: &str) {
+        self.editor.insert(text);
+    }
+
+    ///Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.editor.undo()
+    }
+
+    ///Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.editor.redo()
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    fn look(&self) -> Look {
+        Look {
+            default:  self.default,
+            selected: self.selected,
+            mask:     self.mask,
+            focused:  self.focused,
+        }
+    }
+}
+
+impl Default for ITextInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHandler for ITextInput {
+    fn handle_event(&mut self, event: &Event) -> EventResult {
+        self.editor.one_word = self.mask.is_some();
+
+        let used = match event.input {
+            Input::KeyBoard(key) => self.editor.handle_key(key),
+            Input::Paste(text) => {
+                self.editor.insert(text);
+                true
+            }
+            Input::Mouse(e) => match (e.kind, event.local) {
+                (MouseEventKind::Down(MouseButton::Left), Some(local)) => {
+                    let column = self.scroll.get() + local.x.max(0) as usize;
+                    self.editor.set_cursor(self.editor.at_column(0, column, self.mask));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if used { EventResult::Consumed }
+           else { EventResult::Ignored }
+    }
+
+    fn focus_changed(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::layout::{ILayout, Object};
+    use crate::input::Router;
+    use crate::modifiers::position;
+    use crate::test_helpers::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+
+    fn draw(input: &TextInput, size: Coord) -> ScreenBuf {
+        let mut buf = ScreenBuf::new(size);
+        input.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+        buf
+    }
+
+    fn key(code: KeyCode) -> Input {
+        Input::KeyBoard(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn typing_and_scrolling() {
+        let input = new();
+        input.borrow_mut().placeholder = "name".to_string();
+
+        let mut layout = ILayout::new();
+        let id = layout.add(Object::new(input.clone(), position::craft().size(Coord{x: 5, y: 1}).done()));
+
+        let mut router = Router::new();
+        router.register(id, input.clone());
+
+        //the cursor only shows once focused.
+        assert_chars(&draw(&input, Coord{x: 5, y: 1}).buffer, "name ");
+        router.set_focus(Some(id));
+        assert!(input.borrow().focused());
+
+        for c in "abcdefg".chars() {
+            assert_eq!(router.handle(&key(KeyCode::Char(c)), &layout, None), EventResult::Consumed);
+        }
+
+        //the end of the text is in view, with room for the cursor.
+        let buf = draw(&input, Coord{x: 5, y: 1});
+        assert_chars(&buf.buffer, "defg ");
+        assert_eq!(buf.buffer.get(Coord{x: 4, y: 0}), Pixel::new(' ', Color::Rgb{r: 0, g: 0, b: 0}, Color::Rgb{r: 255, g: 255, b: 255}));
+
+        router.handle(&key(KeyCode::Home), &layout, None);
+        assert_chars(&draw(&input, Coord{x: 5, y: 1}).buffer, "abcde");
+
+        //enter isn't used so it goes on to the parents.
+        assert_eq!(router.handle(&key(KeyCode::Enter), &layout, None), EventResult::Ignored);
+
+        router.handle(&Input::Paste("x\ny".to_string()), &layout, None);
+        assert_eq!(input.borrow().text(), "x yabcdefg");
+
+        //clicking puts the cursor under the mouse.
+        let click = Input::Mouse(MouseEvent {
+            kind:      MouseEventKind::Down(MouseButton::Left),
+            column:    2,
+            row:       0,
+            modifiers: KeyModifiers::NONE,
+        });
+        let hit = layout.hit_test(Coord{x: 2, y: 0}, Coord{x: 5, y: 1}, false);
+        router.handle(&click, &layout, hit);
+        assert_eq!(input.borrow().cursor(), 2);
+
+        input.borrow_mut().select_all();
+        assert_eq!(input.borrow().selected_text(), "x yabcdefg");
+        assert!(input.borrow_mut().undo());
+        assert_eq!(input.borrow().text(), "abcdefg");
+    }
+
+    #[test]
+    fn masked() {
+        let input = new();
+
+        {
+            let mut temp = input.borrow_mut();
+
+            temp.mask = Some('*');
+            temp.set_text("pass word");
+            temp.focus_changed(true);
+        }
+
+        assert_chars(&draw(&input, Coord{x: 12, y: 2}).buffer, "
+*********   
+            ");
+
+        let left = Input::KeyBoard(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        let mut layout = ILayout::new();
+        let id = layout.add(Object::new(input.clone(), position::new()));
+
+        input.borrow_mut().handle_event(&Event { input: &left, target: id, local: None });
+        assert_eq!(input.borrow().cursor(), 0);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::prelude::*;
+use crate::frame_types::layout::{ILayout, ObjectId, Hit};
+use crossterm::event::{KeyCode, MouseEventKind};
+
+/// Whether a handler used up an event, ignored events bubble up to the handler of the parent object.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventResult {
+    Consumed,
+    Ignored,
+}
+
+/// An input on its way to a handler.
+pub struct Event<'a> {
+    pub input:  &'a Input,
+    ///The object the event was first sent to, the focused one or the one under the mouse.
+    pub target: ObjectId,
+    ///Where the mouse is in the target's frame, None for events that aren't from the mouse.
+    pub local:  Option<Coord>,
+}
+
+/// Lets something react to inputs routed to a layout object, usually the frame drawn by that object.
+/// - handle_event
+/// - focusable
+/// - focus_changed
+pub trait EventHandler {
+    fn handle_event(&mut self, event: &Event) -> EventResult;
+
+    ///Objects that can't be focused still get mouse events and events bubbling up from their children.
+    fn focusable(&self) -> bool { true }
+
+    fn focus_changed(&mut self, _focused: bool) {}
+}
+
+pub type Handler = Rc<RefCell<dyn EventHandler>>;
+
+/// Sends inputs to the handlers of layout objects.
+/// Keys and pastes go to the focused object, mouse events to the object under the mouse.
+/// Either way an ignored event is passed up to the handlers of the object's parents until one consumes it.
+/// Tab and BackTab move the focus along the objects in the order they were registered if nothing consumes them,
+/// and pressing a mouse button on an object focuses it.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - register
+/// - unregister
+/// - focused
+/// - set_focus
+/// - focus_next
+/// - focus_prev
+/// - handle
+pub struct Router {
+    ///in focus order.
+    handlers: Vec<(ObjectId, Handler)>,
+    focus:    Option<ObjectId>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Router {
+            handlers: Vec::new(),
+            focus:    None,
+        }
+    }
+
+    ///Gives an object a handler, putting it at the end of the focus order. Replaces the handler if the object already had one.
+    pub fn register(&mut self, id: ObjectId, handler: Handler) {
+        match self.handlers.iter_mut().find(|(other, _)| *other == id) {
+            Some(entry) => entry.1 = handler,
+            None => self.handlers.push((id, handler)),
+        }
+    }
+
+    pub fn unregister(&mut self, id: ObjectId) -> Option<Handler> {
+        let index = self.handlers.iter().position(|(other, _)| *other == id)?;
+
+        if self.focus == Some(id) {
+            self.focus = None;
+        }
+
+        Some(self.handlers.remove(index).1)
+    }
+
+    pub fn focused(&self) -> Option<ObjectId> {
+        self.focus
+    }
+
+    ///Focuses the object, or nothing with None. Returns false if the object has no handler or it can't be focused.
+    pub fn set_focus(&mut self, id: Option<ObjectId>) -> bool {
+        if let Some(id) = id {
+            match self.handler(id) {
+                Some(handler) if handler.borrow().focusable() => {}
+                _ => return false,
+            }
+        }
+
+        if self.focus == id { return true }
+
+        if let Some(handler) = self.focus.and_then(|old| self.handler(old)) {
+            handler.borrow_mut().focus_changed(false);
+        }
+
+        self.focus = id;
+
+        if let Some(handler) = id.and_then(|new| self.handler(new)) {
+            handler.borrow_mut().focus_changed(true);
+        }
+
+        true
+    }
+
+    ///Moves the focus to the next object that can be focused and is shown, going back to the first after the last.
+    pub fn focus_next(&mut self, layout: &ILayout) -> bool {
+        self.step_focus(layout, true)
+    }
+
+    ///Moves the focus to the previous object that can be focused and is shown, going to the last before the first.
+    pub fn focus_prev(&mut self, layout: &ILayout) -> bool {
+        self.step_focus(layout, false)
+    }
+
+    ///Routes an input, hit should be the result of a hit test for mouse inputs and is ignored otherwise.
+    pub fn handle(&mut self, input: &Input, layout: &ILayout, hit: Option<Hit>) -> EventResult {
+        //handlers for objects that were removed from the layout are dropped.
+        let gone: Vec<ObjectId> = self.handlers.iter().map(|(id, _)| *id).filter(|id| layout.get(*id).is_none()).collect();
+        for id in gone {
+            self.unregister(id);
+        }
+
+        if let Input::Mouse(e) = input {
+            let hit = match hit {
+                Some(hit) => hit,
+                None => return EventResult::Ignored,
+            };
+
+            if let MouseEventKind::Down(_) = e.kind {
+                let focusable = self.chain(layout, hit.id).find(|id| {
+                    matches!(self.handler(*id), Some(handler) if handler.borrow().focusable())
+                });
+
+                if let Some(id) = focusable {
+                    self.set_focus(Some(id));
+                }
+            }
+
+            return self.bubble(layout, &Event { input, target: hit.id, local: Some(hit.local) })
+        }
+
+        let result = match self.focus {
+            Some(target) => self.bubble(layout, &Event { input, target, local: None }),
+            None => EventResult::Ignored,
+        };
+
+        if result == EventResult::Consumed { return result }
+
+        match input {
+            Input::KeyBoard(e) if e.code == KeyCode::Tab => { self.focus_next(layout); EventResult::Consumed }
+            Input::KeyBoard(e) if e.code == KeyCode::BackTab => { self.focus_prev(layout); EventResult::Consumed }
+            _ => result,
+        }
+    }
+
+    fn handler(&self, id: ObjectId) -> Option<Handler> {
+        self.handlers.iter().find(|(other, _)| *other == id).map(|(_, handler)| handler.clone())
+    }
+
+    ///The object followed by each of its parents.
+    fn chain<'a>(&self, layout: &'a ILayout, id: ObjectId) -> impl Iterator<Item = ObjectId> + 'a {
+        std::iter::successors(Some(id), move |id| layout.parent(*id))
+    }
+
+    fn bubble(&self, layout: &ILayout, event: &Event) -> EventResult {
+        for id in self.chain(layout, event.target) {
+            if let Some(handler) = self.handler(id) {
+                if handler.borrow_mut().handle_event(event) == EventResult::Consumed {
+                    return EventResult::Consumed
+                }
+            }
+        }
+
+        EventResult::Ignored
+    }
+
+    ///An object is shown if it and all of its parents are enabled.
+    fn shown(&self, layout: &ILayout, id: ObjectId) -> bool {
+        self.chain(layout, id).all(|id| {
+            matches!(layout.get(id), Some(obj) if obj.pos.borrow().data.enabled)
+        })
+    }
+
+    fn step_focus(&mut self, layout: &ILayout, forward: bool) -> bool {
+        let len = self.handlers.len();
+        if len == 0 { return false }
+
+        let current = self.focus.and_then(|id| self.handlers.iter().position(|(other, _)| *other == id));
+
+        for step in 1..=len {
+            let index = match (current, forward) {
+                (Some(i), true)  => (i + step) % len,
+                (Some(i), false) => (i + len - step % len) % len,
+                (None, true)     => step - 1,
+                (None, false)    => len - step,
+            };
+
+            let (id, handler) = &self.handlers[index];
+            if handler.borrow().focusable() && self.shown(layout, *id) {
+                let id = *id;
+                return self.set_focus(Some(id))
+            }
+        }
+
+        false
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::{fill, layout::Object};
+    use crate::modifiers::position;
+    use crossterm::event::{KeyEvent, KeyModifiers, MouseEvent, MouseButton};
+
+    type Log = Rc<RefCell<Vec<(char, ObjectId, Option<Coord>)>>>;
+
+    ///Records what it got and consumes keys matching its name.
+    struct Recorder {
+        name:      char,
+        focusable: bool,
+        focused:   bool,
+        log:       Log,
+    }
+
+    impl EventHandler for Recorder {
+        fn handle_event(&mut self, event: &Event) -> EventResult {
+            self.log.borrow_mut().push((self.name, event.target, event.local));
+
+            match event.input {
+                Input::KeyBoard(e) if e.code == KeyCode::Char(self.name) => EventResult::Consumed,
+                _ => EventResult::Ignored,
+            }
+        }
+
+        fn focusable(&self) -> bool { self.focusable }
+
+        fn focus_changed(&mut self, focused: bool) {
+            self.focused = focused;
+        }
+    }
+
+    fn key(code: KeyCode) -> Input {
+        Input::KeyBoard(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn click(layout: &ILayout, x: u16, y: u16) -> (Input, Option<Hit>) {
+        let input = Input::Mouse(MouseEvent {
+            kind:      MouseEventKind::Down(MouseButton::Left),
+            column:    x,
+            row:       y,
+            modifiers: KeyModifiers::NONE,
+        });
+
+        (input, layout.hit_test(Coord{x: x as i32, y: y as i32}, Coord{x: 6, y: 2}, false))
+    }
+
+    #[test]
+    fn focus_and_bubbling() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut layout = ILayout::new();
+        let mut router = Router::new();
+
+        let mut add = |layout: &mut ILayout, parent: Option<ObjectId>, name: char, x: i32, focusable: bool| {
+            let pos = position::craft().pos(Coord{x, y: 0}).size(Coord{x: 2, y: 2}).done();
+            let obj = Object::new(fill::new(Pixel::new(name, Color::White, Color::Black)), pos);
+
+            let id = match parent {
+                Some(parent) => layout.add_child(parent, obj).unwrap(),
+                None => layout.add(obj),
+            };
+
+            let handler = wrap(Recorder { name, focusable, focused: false, log: log.clone() });
+            router.register(id, handler.clone());
+            (id, handler)
+        };
+
+        let (panel, _) = add(&mut layout, None, 'p', 0, false);
+        let (a, a_handler) = add(&mut layout, Some(panel), 'a', 0, true);
+        let (b, _) = add(&mut layout, None, 'b', 2, true);
+        let (c, _) = add(&mut layout, None, 'c', 4, true);
+
+        //tab skips the panel that can't be focused and wraps around.
+        assert_eq!(router.handle(&key(KeyCode::Tab), &layout, None), EventResult::Consumed);
+        assert_eq!(router.focused(), Some(a));
+        assert!(a_handler.borrow().focused);
+        router.handle(&key(KeyCode::BackTab), &layout, None);
+        assert_eq!(router.focused(), Some(c));
+        router.handle(&key(KeyCode::Tab), &layout, None);
+        assert_eq!(router.focused(), Some(a));
+        assert!(a_handler.borrow().focused);
+
+        //hidden objects are skipped.
+        layout.set_enabled(b, false);
+        router.handle(&key(KeyCode::Tab), &layout, None);
+        assert_eq!(router.focused(), Some(c));
+        layout.set_enabled(b, true);
+
+        //a ignores p so it goes up to the panel, which consumes it.
+        log.borrow_mut().clear();
+        let (input, hit) = click(&layout, 1, 1);
+        assert_eq!(router.handle(&input, &layout, hit), EventResult::Ignored);
+        assert_eq!(router.focused(), Some(a));
+        assert!(a_handler.borrow().focused);
+
+        assert_eq!(router.handle(&key(KeyCode::Char('p')), &layout, None), EventResult::Consumed);
+        assert_eq!(*log.borrow(), vec![
+            ('a', a, Some(Coord{x: 1, y: 1})),
+            ('p', a, Some(Coord{x: 1, y: 1})),
+            ('a', a, None),
+            ('p', a, None),
+        ]);
+
+        //nothing takes x.
+        assert_eq!(router.handle(&key(KeyCode::Char('x')), &layout, None), EventResult::Ignored);
+
+        //removed objects lose their handlers and the focus.
+        layout.remove(panel);
+        router.handle(&key(KeyCode::Char('x')), &layout, None);
+        assert_eq!(router.focused(), None);
+        router.handle(&key(KeyCode::Tab), &layout, None);
+        assert_eq!(router.focused(), Some(b));
+    }
+}
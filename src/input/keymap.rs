@@ -0,0 +1,494 @@
+use crate::prelude::*;
+use crossterm::event::{KeyCode, KeyModifiers, KeyEventKind};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A key with the modifiers held down with it, parsed from text like "ctrl+s", "alt+enter" or "G".
+/// Shift is folded into characters, so "shift+g" and "G" are the same combo.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub code:      KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        //terminals send shift+tab as BackTab and shifted characters as the upper case character, with or without shift.
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::Char(c.to_uppercase().next().unwrap_or(c)), modifiers - KeyModifiers::SHIFT)
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT),
+            KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+
+        KeyCombo { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyCombo {
+    fn from(event: KeyEvent) -> Self {
+        KeyCombo::new(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || KeymapError::BadKey(s.to_string());
+
+        //a trailing + is the + key, as in "ctrl++" or just "+".
+        let (mods, key) = match s.strip_suffix("++") {
+            Some(mods) => (mods, "+"),
+            None if s == "+" => ("", "+"),
+            None => match s.rsplit_once('+') {
+                Some((mods, key)) => (mods, key),
+                None => ("", s),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in mods.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta"     => KeyModifiers::ALT,
+                "shift"            => KeyModifiers::SHIFT,
+                "super"            => KeyModifiers::SUPER,
+                _ => return Err(bad()),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "enter" | "return"   => KeyCode::Enter,
+                "esc" | "escape"     => KeyCode::Esc,
+                "tab"                => KeyCode::Tab,
+                "backtab"            => KeyCode::BackTab,
+                "backspace"          => KeyCode::Backspace,
+                "delete" | "del"     => KeyCode::Delete,
+                "insert" | "ins"     => KeyCode::Insert,
+                "home"               => KeyCode::Home,
+                "end"                => KeyCode::End,
+                "pageup" | "pgup"    => KeyCode::PageUp,
+                "pagedown" | "pgdn"  => KeyCode::PageDown,
+                "up"                 => KeyCode::Up,
+                "down"               => KeyCode::Down,
+                "left"               => KeyCode::Left,
+                "right"              => KeyCode::Right,
+                "space"              => KeyCode::Char(' '),
+                "plus"               => KeyCode::Char('+'),
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=24).contains(&n) => KeyCode::F(n),
+                    _ => return Err(bad()),
+                },
+            },
+        };
+
+        Ok(KeyCombo::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl"), (KeyModifiers::ALT, "alt"), (KeyModifiers::SHIFT, "shift"), (KeyModifiers::SUPER, "super")] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c)   => write!(f, "{}", c),
+            KeyCode::F(n)      => write!(f, "f{}", n),
+            KeyCode::Enter     => write!(f, "enter"),
+            KeyCode::Esc       => write!(f, "esc"),
+            KeyCode::Tab       => write!(f, "tab"),
+            KeyCode::BackTab   => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete    => write!(f, "delete"),
+            KeyCode::Insert    => write!(f, "insert"),
+            KeyCode::Home      => write!(f, "home"),
+            KeyCode::End       => write!(f, "end"),
+            KeyCode::PageUp    => write!(f, "pageup"),
+            KeyCode::PageDown  => write!(f, "pagedown"),
+            KeyCode::Up        => write!(f, "up"),
+            KeyCode::Down      => write!(f, "down"),
+            KeyCode::Left      => write!(f, "left"),
+            KeyCode::Right     => write!(f, "right"),
+            code               => write!(f, "{:?}", code),
+        }
+    }
+}
+
+///Parses keys separated by spaces, like "g g" or "ctrl+x ctrl+s".
+pub fn parse_keys(keys: &str) -> Result<Vec<KeyCombo>, KeymapError> {
+    let combos = keys.split_whitespace().map(KeyCombo::from_str).collect::<Result<Vec<_>, _>>()?;
+
+    if combos.is_empty() {
+        return Err(KeymapError::BadKey(keys.to_string()))
+    }
+
+    Ok(combos)
+}
+
+fn keys_to_string(keys: &[KeyCombo]) -> String {
+    keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeymapError {
+    ///A key that couldn't be parsed.
+    BadKey(String),
+    ///A line of a config that isn't a mode, a binding or a comment.
+    BadLine(usize, String),
+    ///An action the action type couldn't parse.
+    BadAction(usize, String),
+    ///Two bindings in a mode where one is the same as or starts with the other, so the second could never be reached.
+    Conflict {
+        mode:   String,
+        first:  String,
+        second: String,
+    },
+    Io(String),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeymapError::BadKey(key)             => write!(f, "unknown key \"{}\"", key),
+            KeymapError::BadLine(line, text)     => write!(f, "line {}: expected \"[mode]\" or \"keys = action\", found \"{}\"", line, text),
+            KeymapError::BadAction(line, action) => write!(f, "line {}: unknown action \"{}\"", line, action),
+            KeymapError::Conflict { mode, first, second } => write!(f, "in mode {}: \"{}\" conflicts with \"{}\"", mode, second, first),
+            KeymapError::Io(e)                   => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// What a key press did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyResult<A> {
+    ///The keys so far finish a binding.
+    Action(A),
+    ///The keys so far start one or more bindings, waiting for the next key.
+    Pending,
+    ///Nothing is bound to the keys, the pending keys are dropped.
+    Unbound,
+}
+
+/// Turns key presses into actions, with bindings grouped into modes.
+/// Modes are kept on a stack, bindings in the top mode are tried first, then the ones below it,
+/// so a menu mode only needs the keys that act differently in the menu.
+/// Bindings can be loaded from a config like
+/// ```text
+/// # comments start with #
+/// [normal]
+/// g g    = top
+/// ctrl+s = save
+///
+/// [insert]
+/// esc = leave
+/// ```
+/// where the actions are parsed with FromStr.
+/// ## Functions
+/// - new
+/// - from_config
+/// - from_file
+///
+/// ## Methods
+/// - bind
+/// - unbind
+/// - load
+/// - mode
+/// - push_mode
+/// - pop_mode
+/// - set_mode
+/// - pending
+/// - handle
+/// - handle_key
+pub struct Keymap<A> {
+    modes:   HashMap<String, Vec<(Vec<KeyCombo>, A)>>,
+    stack:   Vec<String>,
+    pending: Vec<KeyCombo>,
+}
+
+impl<A: Clone> Keymap<A> {
+    ///A keymap starting in the given mode.
+    pub fn new(mode: &str) -> Self {
+        Keymap {
+            modes:   HashMap::new(),
+            stack:   vec![mode.to_string()],
+            pending: Vec::new(),
+        }
+    }
+
+    ///Binds keys like "ctrl+s" or "g g" to an action in a mode.
+    ///Fails if the keys are already bound in that mode, or start or are the start of keys that are.
+    pub fn bind(&mut self, mode: &str, keys: &str, action: A) -> Result<(), KeymapError> {
+        let keys = parse_keys(keys)?;
+        let bindings = self.modes.entry(mode.to_string()).or_default();
+
+        let shorter = |a: &[KeyCombo], b: &[KeyCombo]| a.len() <= b.len() && b.starts_with(a);
+
+        if let Some((other, _)) = bindings.iter().find(|(other, _)| shorter(other, &keys) || shorter(&keys, other)) {
+            return Err(KeymapError::Conflict {
+                mode:   mode.to_string(),
+                first:  keys_to_string(other),
+                second: keys_to_string(&keys),
+            })
+        }
+
+        bindings.push((keys, action));
+        Ok(())
+    }
+
+    ///Removes a binding, returning its action.
+    pub fn unbind(&mut self, mode: &str, keys: &str) -> Result<Option<A>, KeymapError> {
+        let keys = parse_keys(keys)?;
+        let bindings = match self.modes.get_mut(mode) {
+            Some(bindings) => bindings,
+            None => return Ok(None),
+        };
+
+        Ok(bindings.iter().position(|(other, _)| *other == keys).map(|i| bindings.remove(i).1))
+    }
+
+    pub fn mode(&self) -> &str {
+        self.stack.last().unwrap()
+    }
+
+    ///Puts a mode on top of the current one.
+    pub fn push_mode(&mut self, mode: &str) {
+        self.stack.push(mode.to_string());
+        self.pending.clear();
+    }
+
+    ///Goes back to the mode under the current one, the last mode is never removed.
+    pub fn pop_mode(&mut self) -> Option<String> {
+        if self.stack.len() == 1 { return None }
+
+        self.pending.clear();
+        self.stack.pop()
+    }
+
+    ///Replaces the current mode.
+    pub fn set_mode(&mut self, mode: &str) {
+        *self.stack.last_mut().unwrap() = mode.to_string();
+        self.pending.clear();
+    }
+
+    ///The keys pressed so far of a binding that isn't finished.
+    pub fn pending(&self) -> &[KeyCombo] {
+        &self.pending
+    }
+
+    ///Handles key presses, other inputs and key releases are Unbound.
+    pub fn handle(&mut self, input: &Input) -> KeyResult<A> {
+        match input {
+            Input::KeyBoard(e) if e.kind != KeyEventKind::Release => self.handle_key(KeyCombo::from(*e)),
+            _ => KeyResult::Unbound,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCombo) -> KeyResult<A> {
+        self.pending.push(key);
+
+        match self.lookup() {
+            KeyResult::Unbound if self.pending.len() > 1 => {
+                //the sequence went nowhere, the last key might start a new one.
+                self.pending.clear();
+                self.handle_key(key)
+            }
+            KeyResult::Pending => KeyResult::Pending,
+            result => {
+                self.pending.clear();
+                result
+            }
+        }
+    }
+
+    fn lookup(&self) -> KeyResult<A> {
+        for mode in self.stack.iter().rev() {
+            let bindings = match self.modes.get(mode) {
+                Some(bindings) => bindings,
+                None => continue,
+            };
+
+            for (keys, action) in bindings {
+                if *keys == self.pending {
+                    return KeyResult::Action(action.clone())
+                }
+                if keys.starts_with(&self.pending) {
+                    return KeyResult::Pending
+                }
+            }
+        }
+
+        KeyResult::Unbound
+    }
+}
+
+impl<A: Clone + FromStr> Keymap<A> {
+    ///A keymap with the bindings in the config, starting in the first mode in it.
+    pub fn from_config(config: &str) -> Result<Self, KeymapError> {
+        let first = config.lines()
+            .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+            .next()
+            .unwrap_or("normal");
+
+        let mut keymap = Keymap::new(first.trim());
+        keymap.load(config)?;
+        Ok(keymap)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, KeymapError> {
+        let config = std::fs::read_to_string(path).map_err(|e| KeymapError::Io(e.to_string()))?;
+        Keymap::from_config(&config)
+    }
+
+    ///Adds the bindings in a config, bindings before the first mode go into the current mode.
+    pub fn load(&mut self, config: &str) -> Result<(), KeymapError> {
+        let mut mode = self.mode().to_string();
+
+        for (i, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                mode = name.trim().to_string();
+                continue
+            }
+
+            //split on the last = so = can be bound.
+            let (keys, action) = match line.rsplit_once('=') {
+                Some((keys, action)) if !keys.trim().is_empty() => (keys.trim(), action.trim()),
+                _ => return Err(KeymapError::BadLine(i + 1, line.to_string())),
+            };
+
+            let action = action.parse().map_err(|_| KeymapError::BadAction(i + 1, action.to_string()))?;
+            self.bind(&mode, keys, action)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Action {
+        Top,
+        Save,
+        Down,
+        Insert,
+        Leave,
+        Close,
+    }
+
+    impl FromStr for Action {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(match s {
+                "top"    => Action::Top,
+                "save"   => Action::Save,
+                "down"   => Action::Down,
+                "insert" => Action::Insert,
+                "leave"  => Action::Leave,
+                "close"  => Action::Close,
+                _ => return Err(()),
+            })
+        }
+    }
+
+    fn press(keymap: &mut Keymap<Action>, keys: &str) -> Vec<KeyResult<Action>> {
+        parse_keys(keys).unwrap().into_iter().map(|key| keymap.handle_key(key)).collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("ctrl+s".parse(), Ok(KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert_eq!("shift+g".parse::<KeyCombo>(), "G".parse());
+        assert_eq!("shift+tab".parse(), Ok(KeyCombo::new(KeyCode::BackTab, KeyModifiers::NONE)));
+        assert_eq!("Alt+F5".parse(), Ok(KeyCombo::new(KeyCode::F(5), KeyModifiers::ALT)));
+        assert_eq!("ctrl++".parse(), Ok(KeyCombo::new(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        assert_eq!("+".parse(), Ok(KeyCombo::new(KeyCode::Char('+'), KeyModifiers::NONE)));
+
+        //what is displayed can be parsed back.
+        for combo in ["+", "ctrl++", "alt+space"] {
+            let parsed: KeyCombo = combo.parse().unwrap();
+            assert_eq!(parsed.to_string().parse(), Ok(parsed));
+        }
+        assert_eq!("hyper+x".parse::<KeyCombo>(), Err(KeymapError::BadKey("hyper+x".to_string())));
+        assert_eq!("f25".parse::<KeyCombo>(), Err(KeymapError::BadKey("f25".to_string())));
+
+        //what a terminal sends for shift+g.
+        assert_eq!(KeyCombo::from(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)), "G".parse().unwrap());
+
+        assert_eq!(keys_to_string(&parse_keys("ctrl+x  alt+space g").unwrap()), "ctrl+x alt+space g");
+    }
+
+    #[test]
+    fn sequences_and_modes() {
+        let mut keymap = Keymap::from_config("
+# movement
+[normal]
+g g    = top
+j      = down
+ctrl+s = save
+i      = insert
+
+[insert]
+esc = leave
+
+[menu]
+esc = close
+").unwrap();
+
+        assert_eq!(keymap.mode(), "normal");
+        assert_eq!(press(&mut keymap, "g g j"), vec![KeyResult::Pending, KeyResult::Action(Action::Top), KeyResult::Action(Action::Down)]);
+        assert_eq!(press(&mut keymap, "ctrl+s x"), vec![KeyResult::Action(Action::Save), KeyResult::Unbound]);
+
+        //a broken sequence drops the keys before the last one.
+        assert_eq!(press(&mut keymap, "g j"), vec![KeyResult::Pending, KeyResult::Action(Action::Down)]);
+
+        //modes on top fall through to the ones under them.
+        keymap.push_mode("menu");
+        assert_eq!(press(&mut keymap, "esc j"), vec![KeyResult::Action(Action::Close), KeyResult::Action(Action::Down)]);
+        assert_eq!(keymap.pop_mode(), Some("menu".to_string()));
+        assert_eq!(keymap.pop_mode(), None);
+
+        keymap.set_mode("insert");
+        assert_eq!(press(&mut keymap, "j esc"), vec![KeyResult::Unbound, KeyResult::Action(Action::Leave)]);
+
+        assert_eq!(keymap.unbind("insert", "esc"), Ok(Some(Action::Leave)));
+        assert_eq!(press(&mut keymap, "esc"), vec![KeyResult::Unbound]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut keymap: Keymap<Action> = Keymap::new("normal");
+        keymap.bind("normal", "g g", Action::Top).unwrap();
+
+        assert_eq!(keymap.bind("normal", "g", Action::Down), Err(KeymapError::Conflict {
+            mode:   "normal".to_string(),
+            first:  "g g".to_string(),
+            second: "g".to_string(),
+        }));
+        assert!(keymap.bind("normal", "g g x", Action::Down).is_err());
+        assert!(keymap.bind("menu", "g", Action::Down).is_ok());
+
+        assert_eq!(Keymap::<Action>::from_config("[normal]\nj = jump").err(), Some(KeymapError::BadAction(2, "jump".to_string())));
+        assert_eq!(Keymap::<Action>::from_config("j down").err(), Some(KeymapError::BadLine(1, "j down".to_string())));
+        assert_eq!(Keymap::<Action>::from_config("[a]\nj = down\nj = top").err().unwrap().to_string(), "in mode a: \"j\" conflicts with \"j\"");
+    }
+}
@@ -0,0 +1,5 @@
+pub mod focus;
+pub use focus::*;
+
+pub mod keymap;
+pub use keymap::{Keymap, KeyCombo, KeyResult, KeymapError};
@@ -7,9 +7,20 @@
 //!   - Bundle
 //!   - Fill
 //!   - Layout
+//!   - Split
 //!   - Text
+//!   - Text Input
+//!   - Text Area
 //!   - With Modifier
+//! - input
+//!   - Router <EventHandler>
+//!   - Keymap <KeyCombo>
 //! - manager
+//!   - Backend <CrosstermBackend>
+//!   - Viewport
+//!   - Session
+//!   - Runner <App>
+//!   - Events <Message>
 //! - modifiers
 //!   - Position
 //!   - Circle Mask
@@ -19,8 +30,13 @@
 //!   - Modifier <IModifier>
 //!   - Pixel
 //!   - PixelData
+//!   - BlendData <Channel>
 //!   - Input
 //!   - ScreenBuf
+//! - markup
+//!   - Markup <Style>
+//! - ansi
+//!   - Parser
 
 pub mod prelude;
 
@@ -28,6 +44,8 @@ pub mod frame_types;
 
 pub mod modifiers;
 
+pub mod input;
+
 mod manager;
 pub use manager::*;
 
@@ -38,6 +56,12 @@ pub mod test_helpers;
 mod color_string;
 pub use color_string::{ColorString, ColorSlice};
 
+pub mod color;
+
+pub mod markup;
+
+pub mod ansi;
+
 mod vec2d;
 pub use vec2d::Vec2D;
 
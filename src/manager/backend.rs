@@ -0,0 +1,235 @@
+use crate::prelude::*;
+use crate::color::ColorDepth;
+
+use std::io::{stdout, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::{
+    QueueableCommand, ErrorKind,
+    event::{self, Event},
+    style::{Print, SetForegroundColor, SetBackgroundColor, SetAttribute, ResetColor},
+    cursor,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// The attributes frames can use paired with the attribute that turns them back off.
+/// Some attributes share the same off attribute, NormalIntensity turns off both Bold and Dim and NoUnderline both underlines.
+pub const ATTRIBUTES: &[(Attribute, Attribute)] = &[
+    (Attribute::Bold,             Attribute::NormalIntensity),
+    (Attribute::Dim,              Attribute::NormalIntensity),
+    (Attribute::Italic,           Attribute::NoItalic),
+    (Attribute::Underlined,       Attribute::NoUnderline),
+    (Attribute::DoubleUnderlined, Attribute::NoUnderline),
+    (Attribute::SlowBlink,        Attribute::NoBlink),
+    (Attribute::RapidBlink,       Attribute::NoBlink),
+    (Attribute::Reverse,          Attribute::NoReverse),
+    (Attribute::Hidden,           Attribute::NoHidden),
+    (Attribute::CrossedOut,       Attribute::NotCrossedOut),
+];
+
+/// Everything the manager needs from whatever it is drawing onto.
+/// - size
+/// - move_to
+/// - set_fg
+/// - set_bg
+/// - set_attribute
+/// - reset_color
+/// - print
+/// - print_marks
+/// - new_line
+/// - flush
+/// - show_cursor
+/// - set_alt_screen
+/// - poll_event
+/// - read_event
+/// - color_depth
+/// - cursor_position
+pub trait Backend {
+    ///Returns the number of cells that can be drawn to.
+    fn size(&mut self) -> Result<Coord, ErrorKind>;
+
+    ///Moves the cursor to the given cell.
+    fn move_to(&mut self, pos: Coord) -> Result<(), ErrorKind>;
+
+    ///Sets the foreground color used by following prints.
+    fn set_fg(&mut self, color: Color) -> Result<(), ErrorKind>;
+
+    ///Sets the background color used by following prints.
+    fn set_bg(&mut self, color: Color) -> Result<(), ErrorKind>;
+
+    ///Turns on or off an attribute for following prints, Attribute::Reset also resets the colors.
+    fn set_attribute(&mut self, attribute: Attribute) -> Result<(), ErrorKind>;
+
+    ///Returns the colors and attributes to the terminals defaults.
+    fn reset_color(&mut self) -> Result<(), ErrorKind>;
+
+    ///Writes the character at the cursor and moves the cursor right by the width of the character.
+    fn print(&mut self, c: char) -> Result<(), ErrorKind>;
+
+    ///Writes the rest of a grapheme cluster, like combining marks, onto the character that was just printed.
+    fn print_marks(&mut self, marks: &str) -> Result<(), ErrorKind>;
+
+    ///Moves the cursor to the start of the next line, scrolling everything up if it is on the last line.
+    fn new_line(&mut self) -> Result<(), ErrorKind>;
+
+    ///Makes sure everything that has been printed is actually shown.
+    fn flush(&mut self) -> Result<(), ErrorKind>;
+
+    fn show_cursor(&mut self, show: bool) -> Result<(), ErrorKind>;
+
+    ///True goes to alt screen false returns from alt.
+    fn set_alt_screen(&mut self, alt: bool) -> Result<(), ErrorKind>;
+
+    ///Returns true if an event is ready before the duration is up, by default reads from the terminal.
+    fn poll_event(&mut self, duration: Duration) -> Result<bool, ErrorKind> {
+        event::poll(duration)
+    }
+
+    ///Waits for the next event, by default reads from the terminal.
+    fn read_event(&mut self) -> Result<Event, ErrorKind> {
+        event::read()
+    }
+
+    ///How many colors can be shown, by default detected from the environment.
+    fn color_depth(&self) -> ColorDepth {
+        ColorDepth::detect()
+    }
+
+    ///Where the cursor currently is, by default asks the terminal.
+    fn cursor_position(&mut self) -> Result<Coord, ErrorKind> {
+        let (x, y) = cursor::position()?;
+        Ok(Coord{x: x as i32, y: y as i32})
+    }
+}
+
+/// Draws using crossterm commands written to stdout or any other writer.
+/// ## Functions
+/// - new
+/// - with_writer
+///
+/// ## Methods
+/// - writer
+/// - writer_mut
+/// - into_writer
+pub struct CrosstermBackend<W: Write = Stdout> {
+    out:  W,
+    ///None uses the size of the terminal.
+    size: Option<Coord>,
+}
+
+impl CrosstermBackend {
+    /// Draws to stdout using the size of the terminal.
+    pub fn new() -> Self {
+        CrosstermBackend {
+            out:  stdout(),
+            size: None,
+        }
+    }
+}
+
+impl Default for CrosstermBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    /// Draws to the given writer, files, pipes, sockets and buffers don't have a size so it has to be provided.
+    pub fn with_writer(out: W, size: Coord) -> Self {
+        CrosstermBackend {
+            out,
+            size: Some(size),
+        }
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.out
+    }
+
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    pub fn into_writer(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn size(&mut self) -> Result<Coord, ErrorKind> {
+        if let Some(size) = self.size {
+            return Ok(size)
+        }
+
+        let (x, y) = crossterm::terminal::size()?;
+        Ok(Coord{
+            x: (x as i32),
+            y: (y as i32),
+        })
+    }
+
+    fn move_to(&mut self, pos: Coord) -> Result<(), ErrorKind> {
+        self.out.queue(cursor::MoveTo(pos.x as u16, pos.y as u16))?;
+        Ok(())
+    }
+
+    fn set_fg(&mut self, color: Color) -> Result<(), ErrorKind> {
+        self.out.queue(SetForegroundColor(color))?;
+        Ok(())
+    }
+
+    fn set_bg(&mut self, color: Color) -> Result<(), ErrorKind> {
+        self.out.queue(SetBackgroundColor(color))?;
+        Ok(())
+    }
+
+    fn set_attribute(&mut self, attribute: Attribute) -> Result<(), ErrorKind> {
+        self.out.queue(SetAttribute(attribute))?;
+        Ok(())
+    }
+
+    fn reset_color(&mut self) -> Result<(), ErrorKind> {
+        self.out.queue(SetAttribute(Attribute::Reset))?;
+        self.out.queue(ResetColor)?;
+        Ok(())
+    }
+
+    fn print(&mut self, c: char) -> Result<(), ErrorKind> {
+        self.out.queue(Print(c))?;
+        Ok(())
+    }
+
+    fn print_marks(&mut self, marks: &str) -> Result<(), ErrorKind> {
+        self.out.queue(Print(marks))?;
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> Result<(), ErrorKind> {
+        self.out.queue(Print("\r\n"))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        self.out.flush()
+    }
+
+    fn show_cursor(&mut self, show: bool) -> Result<(), ErrorKind> {
+        if show {
+            self.out.queue(cursor::Show)?;
+        }
+        else {
+            self.out.queue(cursor::Hide)?;
+        }
+        Ok(())
+    }
+
+    fn set_alt_screen(&mut self, alt: bool) -> Result<(), ErrorKind> {
+        if alt {
+            self.out.queue(EnterAlternateScreen)?;
+        }
+        else {
+            self.out.queue(LeaveAlternateScreen)?;
+        }
+        self.out.flush()
+    }
+}
@@ -0,0 +1,220 @@
+use crate::prelude::*;
+use super::manager::{ManagerTrait, ErrorKind, to_input};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender, Receiver, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crossterm::event::{self, Event};
+
+///How long the reader waits for an event before checking if it should stop.
+const POLL_TIME: Duration = Duration::from_millis(50);
+
+/// Everything a main loop waits on, coming through one channel.
+#[derive(Debug)]
+pub enum Message<T> {
+    Input(Input),
+    ///The terminal was resized, holds the manager's new size once it has been resized.
+    Resize(Coord),
+    Tick,
+    ///A message sent by the app, from any thread.
+    App(T),
+    ///Reading input failed, the reader stops after sending this.
+    Error(ErrorKind),
+}
+
+/// Reads terminal events on its own thread and sends them down a channel, along with ticks and the app's own messages,
+/// so a main loop can wait on all of them at once without blocking on the terminal.
+/// Nothing else should read terminal input while this is running, including the manager's input functions.
+/// The threads are stopped when this is dropped.
+/// ## Functions
+/// - spawn
+/// - spawn_with
+///
+/// ## Methods
+/// - sender
+/// - start_ticks
+/// - recv
+/// - recv_timeout
+/// - try_recv
+/// - stop
+pub struct Events<T> {
+    sender:   Sender<Message<T>>,
+    receiver: Receiver<Message<T>>,
+    running:  Arc<AtomicBool>,
+    threads:  Vec<JoinHandle<()>>,
+}
+
+///Reads events from the terminal.
+pub fn spawn<T: Send + 'static>() -> Events<T> {
+    spawn_with(|timeout| {
+        if event::poll(timeout)? { Ok(Some(event::read()?)) }
+                            else { Ok(None) }
+    })
+}
+
+///Reads events from source, which should wait up to the given time for an event and return None if there wasn't one.
+pub fn spawn_with<T, F>(mut source: F) -> Events<T>
+where
+    T: Send + 'static,
+    F: FnMut(Duration) -> Result<Option<Event>, ErrorKind> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let running = Arc::new(AtomicBool::new(true));
+
+    let reader = {
+        let sender = sender.clone();
+        let running = running.clone();
+
+        thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                let message = match source(POLL_TIME) {
+                    Ok(Some(Event::Resize(x, y))) => Message::Resize(Coord{x: x as i32, y: y as i32}),
+                    Ok(Some(event)) => match to_input(event) {
+                        Some(input) => Message::Input(input),
+                        None => continue,
+                    },
+                    Ok(None) => continue,
+                    Err(e) => {
+                        let _ = sender.send(Message::Error(e));
+                        return
+                    }
+                };
+
+                //the receiver is gone so no one is listening.
+                if sender.send(message).is_err() { return }
+            }
+        })
+    };
+
+    Events {
+        sender,
+        receiver,
+        running,
+        threads: vec![reader],
+    }
+}
+
+impl<T: Send + 'static> Events<T> {
+    ///A sender for the app's messages, it can be moved to other threads.
+    pub fn sender(&self) -> Sender<Message<T>> {
+        self.sender.clone()
+    }
+
+    ///Sends a Tick every rate until stopped.
+    pub fn start_ticks(&mut self, rate: Duration) {
+        let sender = self.sender.clone();
+        let running = self.running.clone();
+
+        self.threads.push(thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                thread::sleep(rate);
+
+                if sender.send(Message::Tick).is_err() { return }
+            }
+        }));
+    }
+}
+
+impl<T> Events<T> {
+    ///Waits for the next message, resizing the manager first if the terminal was resized.
+    pub fn recv<M: ManagerTrait>(&self, manager: &mut M) -> Message<T> {
+        //self holds a sender so the channel can't be closed.
+        let message = self.receiver.recv().unwrap();
+        handle_resize(manager, message)
+    }
+
+    ///Waits up to the timeout for the next message.
+    pub fn recv_timeout<M: ManagerTrait>(&self, manager: &mut M, timeout: Duration) -> Option<Message<T>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => Some(handle_resize(manager, message)),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    ///Returns the next message if there is one without waiting.
+    pub fn try_recv<M: ManagerTrait>(&self, manager: &mut M) -> Option<Message<T>> {
+        self.receiver.try_recv().ok().map(|message| handle_resize(manager, message))
+    }
+
+    ///Stops the threads and waits for them to finish, the reader can take a moment to notice.
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<T> Drop for Events<T> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn handle_resize<M: ManagerTrait, T>(manager: &mut M, message: Message<T>) -> Message<T> {
+    match message {
+        Message::Resize(size) => {
+            manager.resize(size.x as u16, size.y as u16);
+            Message::Resize(manager.size())
+        }
+        message => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_manager::LayoutManager;
+    use crate::test_helpers::TestBackend;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::VecDeque;
+    use std::io;
+
+    #[test]
+    fn messages() {
+        let mut manager = LayoutManager::with_backend(TestBackend::new(Coord{x: 4, y: 2})).unwrap();
+
+        let key = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        let mut queued = VecDeque::from(vec![Event::Resize(6, 3), Event::Key(key)]);
+
+        let events = spawn_with(move |_| {
+            match queued.pop_front() {
+                Some(event) => Ok(Some(event)),
+                None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed")),
+            }
+        });
+
+        events.sender().send(Message::App(7)).unwrap();
+
+        let mut got = Vec::new();
+        for _ in 0..4 {
+            got.push(events.recv_timeout(&mut manager, Duration::from_secs(5)).unwrap());
+        }
+
+        //the app's message can come before or after the reader's.
+        let app = got.iter().position(|m| matches!(m, Message::App(7))).unwrap();
+        got.remove(app);
+
+        assert!(matches!(got[0], Message::Resize(Coord{x: 6, y: 3})));
+        assert!(matches!(&got[1], Message::Input(Input::KeyBoard(e)) if e.code == KeyCode::Char('q')));
+        assert!(matches!(&got[2], Message::Error(e) if e.to_string() == "closed"));
+        assert_eq!(manager.size(), Coord{x: 6, y: 3});
+
+        assert!(events.try_recv(&mut manager).is_none());
+    }
+
+    #[test]
+    fn ticks() {
+        let mut manager = LayoutManager::with_backend(TestBackend::new(Coord{x: 1, y: 1})).unwrap();
+
+        let mut events: Events<()> = spawn_with(|timeout| { thread::sleep(timeout); Ok(None) });
+        events.start_ticks(Duration::from_millis(1));
+
+        assert!(matches!(events.recv(&mut manager), Message::Tick));
+        events.stop();
+    }
+}
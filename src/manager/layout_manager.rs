@@ -1,16 +1,19 @@
 use crate::prelude::*;
+use crate::color::ColorDepth;
 pub use super::manager::*;
+pub use super::backend::{Backend, CrosstermBackend};
 use crate::frame_types::layout::{self, Layout};
+use crate::input::{Router, EventResult};
 
-pub use crate::frame_types::layout::Object;
+pub use crate::frame_types::layout::{Object, ObjectId, Hit};
 pub use crate::modifiers::position;
 
-pub struct LayoutManager {
+pub struct LayoutManager<B: Backend = CrosstermBackend> {
     pub layout: Layout,
-    manager: Manager,
+    manager: Manager<B>,
 }
 
-impl ManagerTrait for LayoutManager {
+impl<B: Backend> ManagerTrait for LayoutManager<B> {
     ///Calls the update function on the root frame.
     fn update(&mut self) {
         self.manager.update(self.layout.clone())
@@ -36,23 +39,28 @@ impl ManagerTrait for LayoutManager {
         self.manager.draw(self.layout.clone())
     }
 
+    ///Changes the size to match a terminal resized to columns by rows, like from a resize event.
+    fn resize(&mut self, columns: u16, rows: u16) {
+        self.manager.resize(columns, rows)
+    }
+
     ///Returns the next input value automatically handling screen resizes.
-    fn get_input(&mut self) -> Input {
+    fn get_input(&mut self) -> Result<Input, ErrorKind> {
         self.manager.get_input()
     }
 
     ///Returns the next input but returns if the duration is met. automatically handling screen resizes.
-    fn poll_input(&mut self, duration: Duration) -> Option<Input> {
+    fn poll_input(&mut self, duration: Duration) -> Result<Option<Input>, ErrorKind> {
         self.manager.poll_input(duration)
     }
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes.
-    fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration) {
+    fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration) -> Result<(), ErrorKind> {
         self.manager.inputs_over_duration(inputs, duration)
     }
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes. Trying to match the managers target fps.
-    fn fps_input(&mut self, inputs: &mut Vec<Input>) {
+    fn fps_input(&mut self, inputs: &mut Vec<Input>) -> Result<(), ErrorKind> {
         self.manager.fps_input(inputs)
     }
 
@@ -67,16 +75,62 @@ impl ManagerTrait for LayoutManager {
     }
 
     ///When debug mode is on the manager will not return to the primary screen when dropped, so crash logs are not dropped.
+    ///Not needed with a Session, its panic hook leaves the alternate screen before the crash log is printed.
     fn set_debug(&mut self, debug: bool) {
         self.manager.set_debug(debug)
     }
+
+    ///Sets how many colors the terminal can show, colors it can't show are replaced with the closest one it can.
+    fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.manager.set_color_depth(depth)
+    }
 }
 
 impl LayoutManager {
     pub fn new() -> Result<Self, ErrorKind> {
+        Self::with_backend(CrosstermBackend::new())
+    }
+
+    pub fn new_inline(height: u16) -> Result<Self, ErrorKind> {
+        Self::with_viewport(CrosstermBackend::new(), Viewport::Inline(height))
+    }
+}
+
+impl<B: Backend> LayoutManager<B> {
+    pub fn with_backend(backend: B) -> Result<Self, ErrorKind> {
+        Self::with_viewport(backend, Viewport::Fullscreen)
+    }
+
+    pub fn with_viewport(backend: B, viewport: Viewport) -> Result<Self, ErrorKind> {
         Ok(Self {
             layout: layout::new(),
-            manager: Manager::new()?,
+            manager: Manager::with_viewport(backend, viewport)?,
         })
     }
+
+    ///Finds the topmost object at a terminal cell, like the column and row of a mouse event.
+    ///With pixel_accurate clear and masked out parts of objects are skipped.
+    pub fn hit_test(&self, column: u16, row: u16, pixel_accurate: bool) -> Option<Hit> {
+        let point = self.manager.to_viewport(column, row);
+
+        self.layout.borrow().hit_test(point, self.manager.size(), pixel_accurate)
+    }
+
+    ///Sends the input through the router, hit testing mouse inputs against the layout.
+    pub fn route(&self, router: &mut Router, input: &Input) -> EventResult {
+        let hit = match input {
+            Input::Mouse(e) => self.hit_test(e.column, e.row, true),
+            _ => None,
+        };
+
+        router.handle(input, &self.layout.borrow(), hit)
+    }
+
+    pub fn manager(&self) -> &Manager<B> {
+        &self.manager
+    }
+
+    pub fn manager_mut(&mut self) -> &mut Manager<B> {
+        &mut self.manager
+    }
 }
\ No newline at end of file
@@ -2,20 +2,18 @@ const SCREEN_OFFSET: i32 = if cfg!(windows){ 1 }
                                       else { 0 };
 
 use crate::prelude::*;
+use crate::{Vec2D, CoordIter};
+use crate::color::ColorDepth;
+use super::backend::{Backend, CrosstermBackend, ATTRIBUTES};
+use super::runner::{self, App, FrameStats};
 
 use std::{
-    io::{stdout, Write},
     time::Instant,
     thread::sleep,
+    cmp::min,
 };
 
-use crossterm::{
-    QueueableCommand, ExecutableCommand,
-    style::{Print, SetForegroundColor, SetBackgroundColor, ResetColor},
-    event::{read, poll, Event},
-    cursor,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen}
-};
+use crossterm::event::Event;
 
 pub use crossterm::ErrorKind;
 pub use std::time::Duration;
@@ -36,17 +34,20 @@ pub trait ManagerTrait {
     ///Draws all of the areas given by the tasks onto the screen.
     fn draw(&mut self) -> Result<(), ErrorKind>;
 
+    ///Changes the size to match a terminal resized to columns by rows, like from a resize event.
+    fn resize(&mut self, columns: u16, rows: u16);
+
     ///Returns the next input value automatically handling screen resizes.
-    fn get_input(&mut self) -> Input;
+    fn get_input(&mut self) -> Result<Input, ErrorKind>;
 
     ///Returns the next input but returns if the duration is met. automatically handling screen resizes.
-    fn poll_input(&mut self, duration: Duration) -> Option<Input>;
+    fn poll_input(&mut self, duration: Duration) -> Result<Option<Input>, ErrorKind>;
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes.
-    fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration);
+    fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration) -> Result<(), ErrorKind>;
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes. Trying to match the managers target fps.
-    fn fps_input(&mut self, inputs: &mut Vec<Input>);
+    fn fps_input(&mut self, inputs: &mut Vec<Input>) -> Result<(), ErrorKind>;
 
     ///Wait to match the target fps.
     fn fps_wait(&mut self);
@@ -55,12 +56,33 @@ pub trait ManagerTrait {
     fn set_alt_screen(&mut self, alt: bool);
 
     ///When debug mode is on the manager will not return to the primary screen when dropped, so crash logs are not dropped.
+    ///Not needed with a Session, its panic hook leaves the alternate screen before the crash log is printed.
     fn set_debug(&mut self, debug: bool);
+
+    ///Sets how many colors the terminal can show, colors it can't show are replaced with the closest one it can.
+    fn set_color_depth(&mut self, depth: ColorDepth);
+
+    ///Runs the app with the default Runner until it wants to quit.
+    fn run<A: App<Self>>(&mut self, app: &mut A) -> Result<FrameStats, ErrorKind> where Self: Sized {
+        runner::new().run(self, app)
+    }
+}
+
+/// Where on the terminal the manager draws.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Viewport {
+    ///Takes over the whole terminal using the alternate screen.
+    Fullscreen,
+    ///Reserves this many lines below the cursor on the normal screen, what was drawn last is left in the scrollback when the manager is dropped.
+    Inline(u16),
 }
 
 /// The frame Manager holds on to all of the data necessary for drawing frames into the terminal
 /// ## Functions
 /// - new
+/// - new_inline
+/// - with_backend
+/// - with_viewport
 /// 
 /// ## Methods
 /// - update_objects
@@ -72,7 +94,15 @@ pub trait ManagerTrait {
 /// - objects
 /// - add_task
 /// - draw
-pub struct Manager {
+/// - set_color_depth
+/// - color_depth
+/// - viewport
+/// - to_viewport
+/// - backend
+/// - backend_mut
+pub struct Manager<B: Backend = CrosstermBackend> {
+    backend:      B,
+    viewport:     Viewport,
     screenbuf:    ScreenBuf,
     printer:      PixelPrinter,
     size_updated: bool,
@@ -85,15 +115,46 @@ pub struct Manager {
 impl Manager {
     /// Returns a new frame manager, enters a new terminal screen, and is set to update the whole screen on first draw.
     pub fn new() -> Result<Manager, ErrorKind> {
-        let size = screen_size()?;
-        std::io::stdout().execute(EnterAlternateScreen)?;
-        //println!("{:?}", size);
+        Manager::with_backend(CrosstermBackend::new())
+    }
+
+    /// Returns a new frame manager that draws in the given number of lines below the cursor instead of taking over the terminal.
+    pub fn new_inline(height: u16) -> Result<Manager, ErrorKind> {
+        Manager::with_viewport(CrosstermBackend::new(), Viewport::Inline(height))
+    }
+}
+
+impl<B: Backend> Manager<B> {
+    /// Returns a new frame manager that draws to the given backend, and is set to update the whole screen on first draw.
+    /// The color depth is the one the backend reports.
+    pub fn with_backend(backend: B) -> Result<Manager<B>, ErrorKind> {
+        Manager::with_viewport(backend, Viewport::Fullscreen)
+    }
+
+    /// Returns a new frame manager that draws to the given part of the backend, and is set to update the whole screen on first draw.
+    pub fn with_viewport(mut backend: B, viewport: Viewport) -> Result<Manager<B>, ErrorKind> {
+        let mut printer = PixelPrinter::new();
+        printer.depth = backend.color_depth();
+
+        let size = match viewport {
+            Viewport::Fullscreen => {
+                backend.set_alt_screen(true)?;
+                backend.size()?
+            }
+            Viewport::Inline(height) => {
+                let (size, origin) = reserve_lines(&mut backend, height)?;
+                printer.origin = origin;
+                size
+            }
+        };
 
         Ok(Manager {
+            backend,
+            viewport,
             screenbuf:    ScreenBuf::new(size),
-            printer:      PixelPrinter::new(),
+            printer,
             size_updated: true,
-            alt_screen:   true,
+            alt_screen:   viewport == Viewport::Fullscreen,
             fps_last:     Instant::now(),
             fps_target:   Duration::from_secs(1)/30,
             debug:        false,
@@ -107,7 +168,8 @@ impl Manager {
 
     ///Checks if the screen size has changed and if it has sets it to the new size and returns true, else false.
     pub fn match_size(&mut self) -> Result<(), ErrorKind>{
-        self.set_size(screen_size()?);
+        let size = self.backend.size()?;
+        self.set_size(size);
         Ok(())
     }
 
@@ -128,63 +190,69 @@ impl Manager {
             self.size_updated = false;
         }
 
+        //blended pixels combine with whatever is under them, so they would build up on the last frame.
+        self.screenbuf.clear();
+
         let size = self.screenbuf.size();
         root.borrow().get_draw_data(&mut self.screenbuf, Coord{x: 0, y: 0}, size);
 
-        self.printer.print_buffer(&self.screenbuf)?;
+        self.printer.print_buffer(&mut self.backend, &self.screenbuf)?;
         
         Ok(())
     }
 
     ///Returns the next input value automatically handling screen resizes.
-    pub fn get_input(&mut self) -> Input {
+    pub fn get_input(&mut self) -> Result<Input, ErrorKind> {
         loop {
-            if let Some(input) = self.event_to_input() {
-                return input
+            if let Some(input) = self.event_to_input()? {
+                return Ok(input)
             }
         }
     }
 
     ///Returns the next input but returns if the duration is met. automatically handling screen resizes.
-    pub fn poll_input(&mut self, duration: Duration) -> Option<Input> {
-        if let Ok(true) = poll(duration) {
+    pub fn poll_input(&mut self, duration: Duration) -> Result<Option<Input>, ErrorKind> {
+        if self.backend.poll_event(duration)? {
             self.event_to_input()
         }
         else {
-            None
+            Ok(None)
         }
     }
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes.
-    pub fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration) {
+    pub fn inputs_over_duration(&mut self, inputs: &mut Vec<Input>, duration: Duration) -> Result<(), ErrorKind> {
         let time = Instant::now();
         inputs.clear();
 
         while time.elapsed() < duration {
-            if let Some(input) = self.poll_input(duration - time.elapsed()) {
+            if let Some(input) = self.poll_input(duration.saturating_sub(time.elapsed()))? {
                 inputs.push(input)
             }
         }
 
         //collect any left over inputs that can be grabbed without waiting.
-        while let Some(input) = self.poll_input(Duration::from_secs(0)) {
+        while let Some(input) = self.poll_input(Duration::from_secs(0))? {
             inputs.push(input)
         }
+
+        Ok(())
     }
 
     ///Returns a list of all inputs that occurred during the given duration. Automatically handling screen resizes. tries to match targeted fps.
-    pub fn fps_input(&mut self, inputs: &mut Vec<Input>) {
+    pub fn fps_input(&mut self, inputs: &mut Vec<Input>) -> Result<(), ErrorKind> {
         let now = Instant::now();
         let target = self.fps_last + self.fps_target;
 
-        if now < target {
-            self.inputs_over_duration(inputs, target - now);
+        let result = if now < target {
+            self.inputs_over_duration(inputs, target - now)
         }
         else {
-            self.inputs_over_duration(inputs, Duration::from_secs(0));
-        }
+            self.inputs_over_duration(inputs, Duration::from_secs(0))
+        };
 
         self.fps_last = Instant::now();
+        result
     }
 
     ///Wait to match the target fps.
@@ -201,14 +269,17 @@ impl Manager {
     pub fn set_alt_screen(&mut self, alt: bool) {
         if alt != self.alt_screen {
             if alt {
-                let _ = std::io::stdout().execute(EnterAlternateScreen);
+                let _ = self.backend.set_alt_screen(true);
             }
             else {
-                let _ = std::io::stdout().execute(LeaveAlternateScreen);
-                let _ = std::io::stdout().execute(ResetColor);
+                let _ = self.backend.set_alt_screen(false);
+                let _ = self.backend.reset_color();
+                let _ = self.backend.flush();
+                self.printer.reset_style();
             }
 
-            self.alt_screen = alt
+            self.alt_screen = alt;
+            self.printer.force_redraw();
         }
     }
 
@@ -216,108 +287,437 @@ impl Manager {
         self.debug = debug
     }
 
-    /// Changes the size of the screen to the new size, and refreshes the screen on next draw.
-    fn set_size(&mut self, size: Coord) {
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    ///Turns a terminal cell, like the column and row of a mouse event, into a point on the manager's screen.
+    pub fn to_viewport(&self, column: u16, row: u16) -> Coord {
+        Coord{x: column as i32, y: row as i32} - self.printer.origin
+    }
+
+    ///Sets how many colors the terminal can show, colors it can't show are replaced with the closest one it can.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        if depth != self.printer.depth {
+            self.printer.depth = depth;
+            self.printer.force_redraw();
+        }
+    }
+
+    pub fn color_depth(&self) -> ColorDepth {
+        self.printer.depth
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Changes the size of the screen to the new size, and repaints the whole screen on next draw.
+    /// Inline viewports keep their height and are moved up if the screen got too short for them.
+    fn set_size(&mut self, mut size: Coord) {
+        if let Viewport::Inline(height) = self.viewport {
+            let screen_height = size.y;
+            size.y = min(height as i32, screen_height);
+            self.printer.origin.y = min(self.printer.origin.y, screen_height - size.y);
+        }
+
         self.screenbuf.set_size(size);
+        self.printer.force_redraw();
         self.size_updated = true;
     }
 
     /// Sets the size based on the output of crossterm Resize event.
-    fn resize(&mut self, x: u16, y: u16){
+    pub fn resize(&mut self, x: u16, y: u16){
         self.set_size(Coord{x: (x as i32) + SCREEN_OFFSET, y: (y as i32) + SCREEN_OFFSET});
     }
 
-    fn event_to_input(&mut self) -> Option<Input> {
-        use Event::*;
-
-        match read().unwrap() {
-            Resize(x, y) => {
+    fn event_to_input(&mut self) -> Result<Option<Input>, ErrorKind> {
+        match self.backend.read_event()? {
+            Event::Resize(x, y) => {
                 self.resize(x, y);
-                None
-            }
-            Key(e) => {
-                Some(Input::KeyBoard(e))
-            }
-            Mouse(e) => {
-                Some(Input::Mouse(e))
-            }
-            FocusGained => {
-                Some(Input::FocusGained)
-            }
-            FocusLost => {
-                Some(Input::FocusLost)
-            }
-            Paste(s) => {
-                Some(Input::Paste(s))
+                Ok(None)
             }
+            event => Ok(to_input(event)),
+        }
+    }
+}
+
+///Turns a crossterm event into an input, None for resizes which aren't inputs.
+pub(crate) fn to_input(event: Event) -> Option<Input> {
+    use Event::*;
+
+    match event {
+        Resize(..) => {
+            None
+        }
+        Key(e) => {
+            Some(Input::KeyBoard(e))
+        }
+        Mouse(e) => {
+            Some(Input::Mouse(e))
+        }
+        FocusGained => {
+            Some(Input::FocusGained)
+        }
+        FocusLost => {
+            Some(Input::FocusLost)
+        }
+        Paste(s) => {
+            Some(Input::Paste(s))
         }
     }
 }
 
-impl Drop for Manager {
+impl<B: Backend> Drop for Manager<B> {
     fn drop(&mut self) {
         //if we are still in an alternate when manager dies try and return to the normal one.
         if self.alt_screen {
             if !self.debug {
-                let _ = std::io::stdout().execute(LeaveAlternateScreen);
+                let _ = self.backend.set_alt_screen(false);
             }
-            let _ = std::io::stdout().execute(ResetColor);
+            let _ = self.backend.reset_color();
+            let _ = self.backend.flush();
+        }
+        else if let Viewport::Inline(_) = self.viewport {
+            //leave the last frame where it is and put the cursor on the line after it.
+            let last_line = self.printer.origin.y + self.screenbuf.size().y - 1;
+            let _ = self.backend.reset_color();
+            let _ = self.backend.move_to(Coord{x: 0, y: last_line});
+            let _ = self.backend.new_line();
+            let _ = self.backend.flush();
         }
     }
 }
 
+///Makes room for an inline viewport below the cursor, scrolling the terminal if there are not enough lines left.
+///Returns the size of the viewport and where it starts.
+fn reserve_lines<B: Backend>(backend: &mut B, height: u16) -> Result<(Coord, Coord), ErrorKind> {
+    let screen = backend.size()?;
+    let height = min(height as i32, screen.y);
+    let cursor = backend.cursor_position()?;
+
+    //start on a fresh line if something is already on the cursors line.
+    let mut top = if cursor.x > 0 { cursor.y + 1 }
+                             else { cursor.y };
+
+    let overflow = top + height - screen.y;
+    if overflow > 0 {
+        backend.move_to(Coord{x: 0, y: screen.y - 1})?;
+        for _ in 0..overflow {
+            backend.new_line()?;
+        }
+        top -= overflow;
+    }
+
+    backend.flush()?;
+
+    Ok((Coord{x: screen.x, y: height}, Coord{x: 0, y: top}))
+}
+
+/// Keeps a copy of the last buffer that was written to the terminal so only the cells that changed are printed.
 struct PixelPrinter {
-    current_fg: Option<Color>,
-    current_bg: Option<Color>
+    current_fg:  Option<Color>,
+    current_bg:  Option<Color>,
+    ///None until the attributes have been reset once, since what the terminal had before is unknown.
+    current_attributes: Option<Attributes>,
+    last:        Vec2D,
+    full_redraw: bool,
+    ///colors are converted to this right before they are sent.
+    depth:       ColorDepth,
+    ///where the top left of the buffer is on the screen.
+    origin:      Coord,
 }
 
 impl PixelPrinter {
     pub fn new() -> Self {
         PixelPrinter {
-            current_fg: None,
-            current_bg: None,
+            current_fg:  None,
+            current_bg:  None,
+            current_attributes: None,
+            last:        Vec2D::new(Coord{x: 0, y: 0}),
+            full_redraw: true,
+            depth:       ColorDepth::TrueColor,
+            origin:      Coord{x: 0, y: 0},
         }
     }
 
-    pub fn print_pixel(&mut self, pixel: &Pixel) -> Result<(), ErrorKind> {
-        match pixel {
-            Pixel::Clear => {stdout().queue(cursor::MoveRight(1))?;}
-            Pixel::Opaque(data) => {
-                if !(Some(data.fg) == self.current_fg) {
-                    stdout().queue(SetForegroundColor(data.fg))?;
-                    self.current_fg = Some(data.fg);
-                }
+    ///Makes the next print write every cell instead of only the ones that changed.
+    pub fn force_redraw(&mut self) {
+        self.full_redraw = true;
+    }
+
+    ///Forgets the colors and attributes that were sent, for after the backend resets them.
+    pub fn reset_style(&mut self) {
+        self.current_fg = None;
+        self.current_bg = None;
+        self.current_attributes = None;
+    }
+
+    ///Only sends the attributes that changed, turning off an attribute can turn off others that share its off attribute so those get turned back on.
+    fn set_attributes<B: Backend>(&mut self, out: &mut B, attributes: Attributes) -> Result<(), ErrorKind> {
+        let mut current = match self.current_attributes {
+            Some(current) => current,
+            None => {
+                //reset also clears the colors.
+                out.set_attribute(Attribute::Reset)?;
+                self.current_fg = None;
+                self.current_bg = None;
+                Attributes::default()
+            }
+        };
+
+        if current == attributes {
+            self.current_attributes = Some(current);
+            return Ok(())
+        }
+
+        for (on, off) in ATTRIBUTES {
+            if current.has(*on) && !attributes.has(*on) {
+                out.set_attribute(*off)?;
 
-                if !(Some(data.bg) == self.current_bg) {
-                    stdout().queue(SetBackgroundColor(data.bg))?;
-                    self.current_bg = Some(data.bg);
+                for (other, other_off) in ATTRIBUTES {
+                    if other_off == off { current.unset(*other) }
                 }
+            }
+        }
 
-                stdout().queue(Print(data.character))?;
+        for (on, _) in ATTRIBUTES {
+            if attributes.has(*on) && !current.has(*on) {
+                out.set_attribute(*on)?;
+                current.set(*on);
             }
         }
 
+        self.current_attributes = Some(current);
+
         Ok(())
     }
 
-    pub fn print_buffer(&mut self, buf: &ScreenBuf)  -> Result<(), ErrorKind> {
-        stdout().queue(cursor::MoveTo(0, 0))?;
+    fn print_data<B: Backend>(&mut self, out: &mut B, data: &PixelData) -> Result<(), ErrorKind> {
+        self.set_attributes(out, data.attributes)?;
+
+        let fg = self.depth.convert(data.fg);
+        let bg = self.depth.convert(data.bg);
+
+        if Some(fg) != self.current_fg {
+            out.set_fg(fg)?;
+            self.current_fg = Some(fg);
+        }
+
+        if Some(bg) != self.current_bg {
+            out.set_bg(bg)?;
+            self.current_bg = Some(bg);
+        }
+
+        out.print(data.character)?;
 
+        if !data.marks.is_empty() {
+            out.print_marks(data.marks.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn print_buffer<B: Backend>(&mut self, out: &mut B, buf: &ScreenBuf) -> Result<(), ErrorKind> {
         let pix = &buf.buffer;
         let size = pix.size();
 
-        for i in 0..(size.y * size.x) as usize {
-            self.print_pixel(&pix.get_flat(i))?
+        if self.last.size() != size {
+            self.last = Vec2D::new(size);
+            self.full_redraw = true;
         }
 
-        stdout().flush()
+        //where the terminal cursor will be after the last print, None until something is printed.
+        let mut cursor: Option<Coord> = None;
+
+        for pos in CoordIter::new(Coord{x: 0, y: 0}, size) {
+            let pixel = pix.get(pos);
+
+            if let Pixel::Opaque(data) = pixel {
+                if !self.full_redraw && self.last.get(pos) == pixel { continue }
+
+                //only jump when the cell is not directly after the last one printed.
+                if cursor != Some(pos) {
+                    out.move_to(pos + self.origin)?;
+                }
+
+                self.print_data(out, &data)?;
+                self.last.set(pos, pixel);
+
+                //terminals don't all agree on how wide some characters and clusters are, so jump after them instead of trusting the cursor.
+                cursor = if data.width() == 1 && data.marks.is_empty() { Some(Coord{x: pos.x + 1, y: pos.y}) }
+                                                                  else { None };
+            }
+        }
+
+        self.full_redraw = false;
+
+        out.flush()
     }
 }
 
-fn screen_size() -> Result<Coord, ErrorKind> {
-    let (x, y) = crossterm::terminal::size()?;
-    Ok(Coord{
-        x: (x as i32),
-        y: (y as i32),
-    })
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_types::{fill, layout::{self, Object}};
+    use crate::modifiers::position;
+    use crate::test_helpers::TestBackend;
+
+    fn take_output(backend: &mut CrosstermBackend<Vec<u8>>) -> String {
+        String::from_utf8(std::mem::take(backend.writer_mut())).unwrap()
+    }
+
+    #[test]
+    fn only_changed_cells() {
+        let mut buf = ScreenBuf::new(Coord{x: 3, y: 2});
+        let mut printer = PixelPrinter::new();
+        let mut out = CrosstermBackend::with_writer(Vec::new(), Coord{x: 3, y: 2});
+
+        let fill = fill::new(Pixel::new('a', Color::Red, Color::Black));
+        fill.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 3, y: 2});
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+        assert_eq!(take_output(&mut out).matches('a').count(), 6);
+
+        //nothing changed so nothing gets written.
+        printer.print_buffer(&mut out, &buf).unwrap();
+        assert!(take_output(&mut out).is_empty());
+
+        buf.set(Coord{x: 2, y: 1}, Pixel::new('b', Color::Red, Color::Black));
+        buf.set(Coord{x: 0, y: 0}, Pixel::new('c', Color::Red, Color::Black));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new('d', Color::Red, Color::Black));
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+        assert_eq!(take_output(&mut out), "\x1b[1;1Hcd\x1b[2;3Hb");
+
+        printer.force_redraw();
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+        assert_eq!(take_output(&mut out), "\x1b[1;1Hcda\x1b[2;1Haab");
+    }
+
+    #[test]
+    fn minimal_attributes() {
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 1});
+        let mut printer = PixelPrinter::new();
+        let mut out = CrosstermBackend::with_writer(Vec::new(), Coord{x: 4, y: 1});
+
+        let colors = ColorSet::new(Color::Reset, Color::Reset);
+
+        buf.set(Coord{x: 0, y: 0}, Pixel::new_color_set('a', colors.with(Attribute::Bold)));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new_color_set('b', colors.with(Attribute::Bold).with(Attribute::Dim)));
+        buf.set(Coord{x: 2, y: 0}, Pixel::new_color_set('c', colors.with(Attribute::Dim).with(Attribute::Underlined)));
+        buf.set(Coord{x: 3, y: 0}, Pixel::new_color_set('d', colors));
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+
+        assert_eq!(
+            take_output(&mut out),
+            "\x1b[1;1H\x1b[0m\x1b[1m\x1b[39m\x1b[49ma\x1b[2mb\x1b[22m\x1b[2m\x1b[4mc\x1b[22m\x1b[24md"
+        );
+    }
+
+    #[test]
+    fn shared_off_attribute() {
+        let mut buf = ScreenBuf::new(Coord{x: 3, y: 1});
+        let mut printer = PixelPrinter::new();
+        let mut out = CrosstermBackend::with_writer(Vec::new(), Coord{x: 3, y: 1});
+
+        let colors = ColorSet::new(Color::Reset, Color::Reset);
+
+        buf.set(Coord{x: 0, y: 0}, Pixel::new_color_set('a', colors.with(Attribute::Underlined)));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new_color_set('b', colors.with(Attribute::DoubleUnderlined)));
+        buf.set(Coord{x: 2, y: 0}, Pixel::new_color_set('c', colors));
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+
+        //NoUnderline turns off both kinds of underline.
+        assert_eq!(
+            take_output(&mut out),
+            "\x1b[1;1H\x1b[0m\x1b[4m\x1b[39m\x1b[49ma\x1b[24m\x1b[4:2mb\x1b[24mc"
+        );
+    }
+
+    #[test]
+    fn style_after_reset() {
+        let mut manager = Manager::with_backend(TestBackend::new(Coord{x: 2, y: 1})).unwrap();
+        let cell = Pixel::new_color_set('a', ColorSet::new(Color::Red, Color::Black).with(Attribute::Bold));
+
+        manager.draw(fill::new(cell)).unwrap();
+
+        //leaving the alternate screen resets the colors and attributes, so they have to be sent again.
+        manager.set_alt_screen(false);
+        manager.draw(fill::new(cell)).unwrap();
+
+        assert_eq!(manager.backend().buffer().get(Coord{x: 1, y: 0}), cell);
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 1});
+        let mut printer = PixelPrinter::new();
+        let mut out = TestBackend::new(Coord{x: 4, y: 1});
+
+        buf.set(Coord{x: 0, y: 0}, Pixel::new('世', Color::Red, Color::Black));
+        buf.set(Coord{x: 2, y: 0}, Pixel::new('a', Color::Red, Color::Black));
+        buf.set(Coord{x: 3, y: 0}, Pixel::new('界', Color::Red, Color::Black));
+
+        printer.print_buffer(&mut out, &buf).unwrap();
+        //no room for the right half of the last one.
+        out.assert_chars("世a ");
+
+        //covering half of a wide character blanks the other half.
+        buf.set(Coord{x: 1, y: 0}, Pixel::new('b', Color::Red, Color::Black));
+        printer.print_buffer(&mut out, &buf).unwrap();
+        out.assert_chars(" ba ");
+    }
+
+    #[test]
+    fn blends_each_frame() {
+        let layout = layout::new();
+        let size = Coord{x: 1, y: 1};
+        let red = Color::Rgb{r: 255, g: 0, b: 0};
+
+        let base = fill::new(Pixel::new('a', Color::White, Color::Rgb{r: 0, g: 0, b: 0}));
+        layout.borrow_mut().add(Object::new(base.clone(), position::craft().size(size).done()));
+        layout.borrow_mut().add(Object::new(fill::new(Pixel::Blend(BlendData::tint(red, 128))), position::craft().size(size).done()));
+
+        let mut manager = Manager::with_backend(TestBackend::new(size)).unwrap();
+
+        //drawing the same overlay again doesn't tint it twice.
+        for _ in 0..2 {
+            manager.draw(layout.clone()).unwrap();
+            assert_eq!(manager.backend().buffer().get(Coord{x: 0, y: 0}), Pixel::new('a', Color::White, Color::Rgb{r: 128, g: 0, b: 0}));
+        }
+
+        //with nothing drawn under it the overlay doesn't land on the last frame either.
+        base.borrow_mut().pixel = Pixel::Clear;
+        manager.draw(layout).unwrap();
+        assert_eq!(manager.backend().buffer().get(Coord{x: 0, y: 0}), Pixel::new(' ', Color::Reset, red));
+    }
+
+    #[test]
+    fn clusters() {
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 1});
+        let mut printer = PixelPrinter::new();
+        let mut out = CrosstermBackend::with_writer(Vec::new(), Coord{x: 4, y: 1});
+        let mut test = TestBackend::new(Coord{x: 4, y: 1});
+
+        let colors = ColorSet::new(Color::Red, Color::Black);
+        buf.set(Coord{x: 0, y: 0}, Pixel::Opaque(PixelData::cluster("x\u{301}", colors).unwrap()));
+        buf.set(Coord{x: 1, y: 0}, Pixel::Opaque(PixelData::cluster("👨\u{200d}👧", colors).unwrap()));
+        buf.set(Coord{x: 3, y: 0}, Pixel::new('a', Color::Red, Color::Black));
+
+        //the marks follow their character and the cursor is moved after each cluster.
+        printer.print_buffer(&mut out, &buf).unwrap();
+        assert!(take_output(&mut out).ends_with("x\u{301}\x1b[1;2H👨\u{200d}👧\x1b[1;4Ha"));
+
+        printer.force_redraw();
+        printer.print_buffer(&mut test, &buf).unwrap();
+        test.assert_chars("x\u{301}👨\u{200d}👧a");
+    }
+}
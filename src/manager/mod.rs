@@ -1,4 +1,16 @@
 pub mod manager;
 pub use manager::*;
 
-pub mod layout_manager;
\ No newline at end of file
+pub mod backend;
+pub use backend::*;
+
+pub mod layout_manager;
+
+pub mod events;
+pub use events::{Events, Message};
+
+pub mod runner;
+pub use runner::{App, Runner, FrameStats};
+
+pub mod session;
+pub use session::Session;
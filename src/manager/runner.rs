@@ -0,0 +1,265 @@
+use crate::prelude::*;
+use super::manager::{ManagerTrait, ErrorKind};
+
+use std::time::{Duration, Instant};
+
+///Most ticks run before a frame is drawn, if the app falls further behind than this the extra time is dropped.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// An application driven by a Runner.
+/// - init
+/// - handle_input
+/// - tick
+/// - should_quit
+/// - needs_redraw
+/// - drawn
+pub trait App<M: ManagerTrait> {
+    ///Called once before the first frame.
+    fn init(&mut self, _manager: &mut M) {}
+
+    fn handle_input(&mut self, manager: &mut M, input: Input);
+
+    ///Moves the app forward by dt, which is always the runner's tick rate.
+    fn tick(&mut self, manager: &mut M, dt: Duration);
+
+    fn should_quit(&self) -> bool;
+
+    ///Frames are only drawn when this is true or the screen was resized, true by default.
+    fn needs_redraw(&self) -> bool { true }
+
+    ///Called after each frame is drawn.
+    fn drawn(&mut self, _stats: &FrameStats) {}
+}
+
+/// How fast frames are really being drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameStats {
+    ///Frames drawn over the last second.
+    pub fps:        f64,
+    ///Time between the last two frames.
+    pub frame_time: Duration,
+    ///Time the last frame took to draw.
+    pub draw_time:  Duration,
+    pub frames:     u64,
+    pub ticks:      u64,
+    window_start:   Option<Instant>,
+    window_frames:  u32,
+    last_frame:     Option<Instant>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats {
+            fps:           0.0,
+            frame_time:    Duration::ZERO,
+            draw_time:     Duration::ZERO,
+            frames:        0,
+            ticks:         0,
+            window_start:  None,
+            window_frames: 0,
+            last_frame:    None,
+        }
+    }
+
+    fn record_frame(&mut self, start: Instant, end: Instant) {
+        self.frames += 1;
+        self.draw_time = end - start;
+
+        if let Some(last) = self.last_frame {
+            self.frame_time = start - last;
+        }
+        self.last_frame = Some(start);
+
+        let window_start = *self.window_start.get_or_insert(start);
+        self.window_frames += 1;
+
+        let elapsed = end - window_start;
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.window_frames as f64 / elapsed.as_secs_f64();
+            self.window_start = Some(end);
+            self.window_frames = 0;
+        }
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn new() -> Runner {
+    Runner::new()
+}
+
+pub fn craft() -> CraftRunner {
+    CraftRunner(Runner::new())
+}
+
+pub struct CraftRunner (Runner);
+
+impl CraftRunner {
+    ///How often the app is ticked, 30 times a second by default.
+    pub fn tick_rate(mut self, tick_rate: Duration) -> Self {
+        self.0.tick_rate = tick_rate;
+        self
+    }
+
+    ///Most frames drawn a second, 30 by default.
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.0.frame_time = Duration::from_secs(1) / fps.max(1);
+        self
+    }
+
+    pub fn done(self) -> Runner {
+        self.0
+    }
+}
+
+/// Runs an App, ticking it at a fixed rate while drawing as often as the target fps allows.
+/// Inputs are handed to the app as they come in while waiting for the next frame.
+/// ## Functions
+/// - new
+/// - craft
+///
+/// ## Methods
+/// - run
+/// - stats
+pub struct Runner {
+    tick_rate:  Duration,
+    frame_time: Duration,
+    stats:      FrameStats,
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        Runner {
+            tick_rate:  Duration::from_secs(1) / 30,
+            frame_time: Duration::from_secs(1) / 30,
+            stats:      FrameStats::new(),
+        }
+    }
+
+    ///Runs the app until it wants to quit, returning how fast it was drawn.
+    pub fn run<M: ManagerTrait, A: App<M>>(&mut self, manager: &mut M, app: &mut A) -> Result<FrameStats, ErrorKind> {
+        app.init(manager);
+
+        let mut last_tick = Instant::now();
+        let mut behind = Duration::ZERO;
+        let mut redraw = true;
+
+        while !app.should_quit() {
+            let frame_end = Instant::now() + self.frame_time;
+            let size = manager.size();
+
+            //inputs are taken until it is time for the next frame.
+            loop {
+                let now = Instant::now();
+                if now >= frame_end || app.should_quit() { break }
+
+                if let Some(input) = manager.poll_input(frame_end - now)? {
+                    app.handle_input(manager, input);
+                }
+            }
+
+            redraw |= manager.size() != size;
+
+            let now = Instant::now();
+            behind += now - last_tick;
+            last_tick = now;
+
+            let mut ticks = 0;
+            while behind >= self.tick_rate && !app.should_quit() {
+                if ticks == MAX_TICKS_PER_FRAME {
+                    behind = Duration::ZERO;
+                    break
+                }
+
+                app.tick(manager, self.tick_rate);
+                behind -= self.tick_rate;
+                ticks += 1;
+                self.stats.ticks += 1;
+            }
+
+            if app.should_quit() { break }
+
+            if redraw || app.needs_redraw() {
+                let start = Instant::now();
+                manager.draw()?;
+                self.stats.record_frame(start, Instant::now());
+
+                app.drawn(&self.stats);
+                redraw = false;
+            }
+        }
+
+        Ok(self.stats)
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_manager::LayoutManager;
+    use crate::test_helpers::TestBackend;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    type Manager = LayoutManager<TestBackend>;
+
+    struct Counter {
+        inited: bool,
+        keys:   u32,
+        ticks:  u32,
+        redraw: bool,
+    }
+
+    impl App<Manager> for Counter {
+        fn init(&mut self, manager: &mut Manager) {
+            self.inited = true;
+            manager.manager_mut().backend_mut().push_input(Input::KeyBoard(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)));
+        }
+
+        fn handle_input(&mut self, _manager: &mut Manager, _input: Input) {
+            self.keys += 1;
+        }
+
+        fn tick(&mut self, _manager: &mut Manager, dt: Duration) {
+            assert_eq!(dt, Duration::from_millis(1));
+            self.ticks += 1;
+        }
+
+        fn should_quit(&self) -> bool {
+            self.ticks >= 10
+        }
+
+        fn needs_redraw(&self) -> bool {
+            self.redraw
+        }
+    }
+
+    #[test]
+    fn run() {
+        let mut manager = LayoutManager::with_backend(TestBackend::new(Coord{x: 2, y: 1})).unwrap();
+        let mut app = Counter { inited: false, keys: 0, ticks: 0, redraw: false };
+
+        let mut runner = craft().tick_rate(Duration::from_millis(1)).fps(500).done();
+        let stats = runner.run(&mut manager, &mut app).unwrap();
+
+        assert!(app.inited);
+        assert_eq!(app.keys, 1);
+        assert_eq!(app.ticks, 10);
+        assert_eq!(stats.ticks, 10);
+        //only the first frame is drawn when the app never asks for more.
+        assert_eq!(stats.frames, 1);
+    }
+}
@@ -0,0 +1,258 @@
+use std::io::{stdout, Write};
+use std::panic;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, ThreadId};
+
+use crossterm::{
+    QueueableCommand, ErrorKind,
+    event::{EnableMouseCapture, DisableMouseCapture, EnableBracketedPaste, DisableBracketedPaste, EnableFocusChange, DisableFocusChange},
+    style::{ResetColor, SetAttribute, Attribute},
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    cursor,
+};
+
+///What the active session turned on, the panic hook can't be removed again so it uses this to know if there is anything to do.
+static CURRENT: Mutex<Option<Active>> = Mutex::new(None);
+
+///Makes sure the panic hook is only installed once no matter how many sessions are made.
+static HOOKED: AtomicBool = AtomicBool::new(false);
+
+pub fn craft() -> CraftSession {
+    CraftSession(Options::default())
+}
+
+/// Which terminal modes the session turns on and has to turn back off.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Options {
+    raw_mode:   bool,
+    alt_screen: bool,
+    mouse:      bool,
+    paste:      bool,
+    focus:      bool,
+    panic_hook: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            raw_mode:   true,
+            alt_screen: true,
+            mouse:      false,
+            paste:      false,
+            focus:      false,
+            panic_hook: true,
+        }
+    }
+}
+
+///The options of the running session and the thread that started it, only a panic on that thread restores the terminal.
+#[derive(Copy, Clone, Debug)]
+struct Active {
+    options: Options,
+    thread:  ThreadId,
+}
+
+pub struct CraftSession (Options);
+
+impl CraftSession {
+    ///Keys are sent as soon as they are pressed instead of after enter, on by default.
+    pub fn raw_mode(mut self, on: bool) -> Self {
+        self.0.raw_mode = on;
+        self
+    }
+
+    ///Uses the alternate screen, on by default. Turn this off for managers with an inline viewport.
+    pub fn alt_screen(mut self, on: bool) -> Self {
+        self.0.alt_screen = on;
+        self
+    }
+
+    ///Reports mouse clicks, scrolling and movement as inputs.
+    pub fn mouse(mut self, on: bool) -> Self {
+        self.0.mouse = on;
+        self
+    }
+
+    ///Reports pasted text as a single paste input instead of a key press for each character.
+    pub fn bracketed_paste(mut self, on: bool) -> Self {
+        self.0.paste = on;
+        self
+    }
+
+    ///Reports when the terminal gains or loses focus.
+    pub fn focus(mut self, on: bool) -> Self {
+        self.0.focus = on;
+        self
+    }
+
+    ///Restores the terminal before the panic message is printed, on by default.
+    ///Only panics on the thread that starts the session do this, a worker thread dying leaves the terminal alone.
+    ///A panic on that thread that is caught with catch_unwind still restores it, so the session has to be started again to keep drawing.
+    pub fn panic_hook(mut self, on: bool) -> Self {
+        self.0.panic_hook = on;
+        self
+    }
+
+    ///Turns on the chosen modes.
+    pub fn done(self) -> Result<Session, ErrorKind> {
+        Session::start(self.0)
+    }
+}
+
+/// Puts the terminal into the modes an interactive program needs and puts it back when dropped.
+/// With the panic hook the terminal is also put back when the thread that started the session panics, even with panic = abort,
+/// so the panic message ends up on the normal screen.
+/// ## Functions
+/// - craft
+///
+/// ## Methods
+/// - restore
+pub struct Session {
+    options: Options,
+}
+
+impl Session {
+    fn start(options: Options) -> Result<Session, ErrorKind> {
+        if options.panic_hook {
+            install_hook();
+        }
+
+        //the session exists before anything is turned on so a failure part way through still gets undone.
+        let session = Session { options };
+        *current() = Some(Active { options, thread: thread::current().id() });
+
+        if options.raw_mode {
+            terminal::enable_raw_mode()?;
+        }
+
+        let mut out = stdout();
+        enable(&mut out, options)?;
+        out.flush()?;
+
+        Ok(session)
+    }
+
+    ///Puts the terminal back right away, dropping the session afterwards does nothing more.
+    pub fn restore(&mut self) -> Result<(), ErrorKind> {
+        let active = current().take();
+
+        match active {
+            Some(_) => restore_terminal(self.options),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+///A panic while the lock is held leaves it poisoned, the options inside are still fine to use.
+fn current() -> MutexGuard<'static, Option<Active>> {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn install_hook() {
+    if HOOKED.swap(true, Ordering::SeqCst) { return }
+
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        if let Some(options) = take_for_panic(thread::current().id()) {
+            if options.panic_hook {
+                let _ = restore_terminal(options);
+            }
+        }
+
+        previous(info);
+    }));
+}
+
+///Ends the session if the panicking thread is the one that started it.
+fn take_for_panic(thread: ThreadId) -> Option<Options> {
+    let mut active = current();
+
+    match *active {
+        Some(Active { thread: owner, .. }) if owner == thread => active.take().map(|a| a.options),
+        _ => None,
+    }
+}
+
+fn restore_terminal(options: Options) -> Result<(), ErrorKind> {
+    let mut out = stdout();
+    disable(&mut out, options)?;
+    out.flush()?;
+
+    if options.raw_mode {
+        terminal::disable_raw_mode()?;
+    }
+
+    Ok(())
+}
+
+fn enable<W: Write>(out: &mut W, options: Options) -> Result<(), ErrorKind> {
+    if options.alt_screen { out.queue(EnterAlternateScreen)?; }
+    if options.mouse      { out.queue(EnableMouseCapture)?; }
+    if options.paste      { out.queue(EnableBracketedPaste)?; }
+    if options.focus      { out.queue(EnableFocusChange)?; }
+
+    Ok(())
+}
+
+///Turns everything back off in the opposite order, also resetting anything a manager might have left behind.
+fn disable<W: Write>(out: &mut W, options: Options) -> Result<(), ErrorKind> {
+    if options.focus      { out.queue(DisableFocusChange)?; }
+    if options.paste      { out.queue(DisableBracketedPaste)?; }
+    if options.mouse      { out.queue(DisableMouseCapture)?; }
+
+    out.queue(SetAttribute(Attribute::Reset))?;
+    out.queue(ResetColor)?;
+    out.queue(cursor::Show)?;
+
+    if options.alt_screen { out.queue(LeaveAlternateScreen)?; }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enable_and_disable() {
+        let options = Options { raw_mode: false, mouse: true, ..Options::default() };
+
+        let mut on = Vec::new();
+        enable(&mut on, options).unwrap();
+        let on = String::from_utf8(on).unwrap();
+
+        assert!(on.starts_with("\x1b[?1049h"));
+        assert!(on.contains("\x1b[?1000h"));
+        assert!(!on.contains("\x1b[?2004h"));
+
+        let mut off = Vec::new();
+        disable(&mut off, options).unwrap();
+        let off = String::from_utf8(off).unwrap();
+
+        assert!(off.contains("\x1b[?1000l"));
+        assert!(off.contains("\x1b[?25h"));
+        assert!(off.ends_with("\x1b[?1049l"));
+    }
+
+    #[test]
+    fn panics_on_other_threads() {
+        let options = Options::default();
+        let worker = thread::spawn(|| thread::current().id()).join().unwrap();
+
+        *current() = Some(Active { options, thread: thread::current().id() });
+
+        assert_eq!(take_for_panic(worker), None);
+        assert!(current().is_some());
+
+        assert_eq!(take_for_panic(thread::current().id()), Some(options));
+        assert!(current().is_none());
+    }
+}
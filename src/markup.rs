@@ -0,0 +1,469 @@
+//! Builds ColorStrings from markup like `[fg=red bold]Warning[/]: disk full`, and turns them back into markup.
+//!
+//! A tag holds colors, attributes and named styles separated by spaces, and lasts until the matching `[/]`.
+//! Tags can be nested, an inner tag starts from the colors of the one around it.
+//! - `fg=color` and `bg=color` take a color name like `red` or `dark_blue`, `reset`, `#rrggbb` or an ansi value from 0 to 255.
+//! - Attributes are `bold`, `dim`, `italic`, `underline`, `double_underline`, `blink`, `rapid_blink`, `reverse`, `hidden` and `strike`.
+//! - Anything else is looked up in the styles of the Markup.
+//!
+//! `[[` is a literal `[`, a `]` outside of a tag needs no escaping.
+use crate::prelude::*;
+use crate::{ColorString, ColorSlice};
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+
+const ATTRIBUTES: [(&str, Attribute); 10] = [
+    ("bold",             Attribute::Bold),
+    ("dim",              Attribute::Dim),
+    ("italic",           Attribute::Italic),
+    ("underline",        Attribute::Underlined),
+    ("double_underline", Attribute::DoubleUnderlined),
+    ("blink",            Attribute::SlowBlink),
+    ("rapid_blink",      Attribute::RapidBlink),
+    ("reverse",          Attribute::Reverse),
+    ("hidden",           Attribute::Hidden),
+    ("strike",           Attribute::CrossedOut),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkupError {
+    ///A tag at the char index that was never closed.
+    Unclosed(usize),
+    ///A `[/]` at the char index with no tag to close.
+    UnexpectedClose(usize),
+    ///A `[` at the char index without a `]` to end the tag.
+    UnterminatedTag(usize),
+    BadColor(String),
+    ///A word in a tag that isn't a color, an attribute or a style.
+    UnknownStyle(String),
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::Unclosed(pos)        => write!(f, "tag at {} is never closed", pos),
+            MarkupError::UnexpectedClose(pos) => write!(f, "[/] at {} has no tag to close", pos),
+            MarkupError::UnterminatedTag(pos) => write!(f, "tag at {} has no closing ]", pos),
+            MarkupError::BadColor(color)      => write!(f, "unknown color \"{}\"", color),
+            MarkupError::UnknownStyle(style)  => write!(f, "unknown style \"{}\"", style),
+        }
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Changes made to the colors by a tag, colors that aren't set are kept.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    pub fg:         Option<Color>,
+    pub bg:         Option<Color>,
+    pub attributes: Attributes,
+}
+
+impl Style {
+    pub fn apply(&self, mut colors: ColorSet) -> ColorSet {
+        if let Some(fg) = self.fg { colors.fg = fg }
+        if let Some(bg) = self.bg { colors.bg = bg }
+        colors.attributes.extend(self.attributes);
+        colors
+    }
+
+    fn merge(&mut self, other: &Style) {
+        self.fg = other.fg.or(self.fg);
+        self.bg = other.bg.or(self.bg);
+        self.attributes.extend(other.attributes);
+    }
+}
+
+/// Parses markup with a set of named styles.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - style
+/// - parse
+pub struct Markup {
+    ///Colors of text in a tag that doesn't set them, white on black by default.
+    pub default: ColorSet,
+    styles:      HashMap<String, Style>,
+}
+
+impl Markup {
+    pub fn new() -> Self {
+        Markup {
+            default: ColorSet::new(Color::Rgb{r: 255, g: 255, b: 255}, Color::Rgb{r: 0, g: 0, b: 0}),
+            styles:  HashMap::new(),
+        }
+    }
+
+    ///Names the contents of a tag so it can be used in other tags, like `markup.style("warn", "fg=yellow bold")`.
+    pub fn style(&mut self, name: &str, spec: &str) -> Result<(), MarkupError> {
+        let style = self.parse_spec(spec)?;
+        self.styles.insert(name.to_string(), style);
+        Ok(())
+    }
+
+    pub fn parse(&self, markup: &str) -> Result<ColorString, MarkupError> {
+        let chars: Vec<char> = markup.chars().collect();
+
+        let mut string = String::new();
+        let mut len = 0;
+        let mut colors: Vec<ColorSlice> = Vec::new();
+        //colors of each open tag and where it was opened.
+        let mut open: Vec<(ColorSet, usize)> = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+
+            if c == '[' && chars.get(i + 1) != Some(&'[') {
+                let end = chars[i..].iter().position(|c| *c == ']').ok_or(MarkupError::UnterminatedTag(i))? + i;
+                let tag: String = chars[i + 1..end].iter().collect();
+
+                if tag == "/" {
+                    open.pop().ok_or(MarkupError::UnexpectedClose(i))?;
+                }
+                else {
+                    let base = open.last().map_or(self.default, |(colors, _)| *colors);
+                    open.push((self.parse_spec(&tag)?.apply(base), i));
+                }
+
+                i = end + 1;
+                continue
+            }
+
+            string.push(c);
+
+            if let Some((set, _)) = open.last() {
+                match colors.last_mut() {
+                    Some(slice) if slice.end == len && slice.colors == *set => slice.end += 1,
+                    _ => colors.push(ColorSlice { start: len, end: len + 1, colors: *set }),
+                }
+            }
+
+            len += 1;
+            //skips the second [ of an escape.
+            i += if c == '[' { 2 } else { 1 };
+        }
+
+        if let Some((_, pos)) = open.last() {
+            return Err(MarkupError::Unclosed(*pos))
+        }
+
+        Ok(ColorString { string, colors })
+    }
+
+    fn parse_spec(&self, spec: &str) -> Result<Style, MarkupError> {
+        let mut style = Style::default();
+
+        for word in spec.split_whitespace() {
+            if let Some(color) = word.strip_prefix("fg=") {
+                style.fg = Some(parse_color(color)?);
+            }
+            else if let Some(color) = word.strip_prefix("bg=") {
+                style.bg = Some(parse_color(color)?);
+            }
+            else if let Some((_, attribute)) = ATTRIBUTES.iter().find(|(name, _)| *name == word) {
+                style.attributes.set(*attribute);
+            }
+            else if let Some(named) = self.styles.get(word) {
+                style.merge(named);
+            }
+            else {
+                return Err(MarkupError::UnknownStyle(word.to_string()))
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+impl Default for Markup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Parses markup without any named styles.
+pub fn parse(markup: &str) -> Result<ColorString, MarkupError> {
+    Markup::new().parse(markup)
+}
+
+///Escapes text so it shows up as written when parsed.
+pub fn escape(text: &str) -> String {
+    text.replace('[', "[[")
+}
+
+///Writes a ColorString as markup with a tag for each of its color slices, parsing it gives back the same ColorString.
+pub fn to_markup(string: &ColorString) -> String {
+    let chars: Vec<char> = string.string.chars().collect();
+    let text = |start: usize, end: usize| escape(&chars[start..end].iter().collect::<String>());
+
+    let mut out = String::new();
+    let mut pos = 0;
+
+    for slice in &string.colors {
+        let start = slice.start.clamp(pos, chars.len());
+        let end = slice.end.clamp(start, chars.len());
+
+        out += &text(pos, start);
+        out += &format!("[fg={} bg={}", color_name(slice.colors.fg), color_name(slice.colors.bg));
+
+        for (name, attribute) in ATTRIBUTES {
+            if slice.colors.attributes.has(attribute) {
+                out += " ";
+                out += name;
+            }
+        }
+
+        out += "]";
+        out += &text(start, end);
+        out += "[/]";
+
+        pos = end;
+    }
+
+    out += &text(pos, chars.len());
+    out
+}
+
+fn parse_color(color: &str) -> Result<Color, MarkupError> {
+    let bad = || MarkupError::BadColor(color.to_string());
+
+    if color == "reset" {
+        return Ok(Color::Reset)
+    }
+
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() { return Err(bad()) }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| bad());
+        return Ok(Color::Rgb{r: channel(0)?, g: channel(2)?, b: channel(4)?})
+    }
+
+    if let Ok(value) = color.parse::<u8>() {
+        return Ok(Color::AnsiValue(value))
+    }
+
+    Color::try_from(color).map_err(|_| bad())
+}
+
+fn color_name(color: Color) -> String {
+    let name = match color {
+        Color::Reset            => "reset",
+        Color::Black            => "black",
+        Color::DarkGrey         => "dark_grey",
+        Color::Red              => "red",
+        Color::DarkRed          => "dark_red",
+        Color::Green            => "green",
+        Color::DarkGreen        => "dark_green",
+        Color::Yellow           => "yellow",
+        Color::DarkYellow       => "dark_yellow",
+        Color::Blue             => "blue",
+        Color::DarkBlue         => "dark_blue",
+        Color::Magenta          => "magenta",
+        Color::DarkMagenta      => "dark_magenta",
+        Color::Cyan             => "cyan",
+        Color::DarkCyan         => "dark_cyan",
+        Color::White            => "white",
+        Color::Grey             => "grey",
+        Color::Rgb{r, g, b}     => return format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::AnsiValue(value) => return value.to_string(),
+    };
+
+    name.to_string()
+}
+
+///Collects the output of the markup macro, escaping what the arguments write.
+#[doc(hidden)]
+pub struct MarkupWriter<'a> {
+    pub string:   String,
+    pub escaping: &'a Cell<bool>,
+}
+
+impl<'a> fmt::Write for MarkupWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.escaping.get() { self.string += &escape(s) }
+                          else { self.string += s }
+        Ok(())
+    }
+}
+
+///An argument of the markup macro, it turns on escaping while it is written so its format options still work.
+#[doc(hidden)]
+pub struct Escaped<'a, T>(pub &'a T, pub &'a Cell<bool>);
+
+impl<'a, T: fmt::Display> fmt::Display for Escaped<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.1.set(true);
+        let result = self.0.fmt(f);
+        self.1.set(false);
+        result
+    }
+}
+
+///True if the format string captures a variable by name like `{name}`, which the markup macro couldn't escape.
+#[doc(hidden)]
+pub const fn captures_inline(fmt: &str) -> bool {
+    let bytes = fmt.as_bytes();
+    let mut i = 0;
+
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'{' {
+            let next = bytes[i + 1];
+
+            //an escaped brace.
+            if next == b'{' {
+                i += 2;
+                continue
+            }
+
+            if next == b'_' || next.is_ascii_alphabetic() || !next.is_ascii() { return true }
+        }
+
+        i += 1;
+    }
+
+    false
+}
+
+/// Formats markup like `format!`, then parses it into a ColorString.
+/// The arguments are escaped so they show up as written.
+/// Variables can't be captured in the string like `{name}` since they wouldn't be escaped, so that fails to compile.
+/// A Markup with named styles can be given before a semicolon.
+/// ```
+/// # use frames::markup;
+/// let warning = markup!("[fg=yellow bold]{}[/] items left", 3).unwrap();
+///
+/// let mut styles = markup::Markup::new();
+/// styles.style("warn", "fg=yellow bold").unwrap();
+/// let warning = markup!(styles; "[warn]{}[/]", "[low]").unwrap();
+/// assert_eq!(warning.string, "[low]");
+/// ```
+/// ```compile_fail
+/// # use frames::markup;
+/// let user = "[low]";
+/// let greeting = markup!("[bold]{user}[/]");
+/// ```
+#[macro_export]
+macro_rules! markup {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {
+        $crate::markup!($crate::markup::Markup::new(); $fmt $(, $arg)*)
+    };
+    ($markup:expr; $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        const _: () = ::std::assert!(
+            !$crate::markup::captures_inline($fmt),
+            "markup! can't escape variables captured in the string, pass them as arguments instead"
+        );
+
+        let escaping = ::std::cell::Cell::new(false);
+        let mut writer = $crate::markup::MarkupWriter { string: ::std::string::String::new(), escaping: &escaping };
+
+        ::std::fmt::Write::write_fmt(&mut writer, ::std::format_args!($fmt $(, $crate::markup::Escaped(&$arg, &escaping))*))
+            .expect("formatting markup failed");
+
+        $markup.parse(&writer.string)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(start: usize, end: usize, colors: ColorSet) -> ColorSlice {
+        ColorSlice { start, end, colors }
+    }
+
+    #[test]
+    fn tags() {
+        let white = Color::Rgb{r: 255, g: 255, b: 255};
+        let black = Color::Rgb{r: 0, g: 0, b: 0};
+
+        let parsed = parse("a [fg=red bold]b[bg=#0000ff]c[/]d[/] [[e] [dim]f").unwrap_err();
+        assert_eq!(parsed, MarkupError::Unclosed(42));
+
+        let parsed = parse("a [fg=red bold]b[bg=#0000ff]c[/]d[/] [[e] [dim]f[/]").unwrap();
+        let red_bold = ColorSet::new(Color::Red, black).with(Attribute::Bold);
+
+        assert_eq!(parsed.string, "a bcd [e] f");
+        assert_eq!(parsed.colors, vec![
+            slice(2, 3, red_bold),
+            slice(3, 4, ColorSet::new(Color::Red, Color::Rgb{r: 0, g: 0, b: 255}).with(Attribute::Bold)),
+            slice(4, 5, red_bold),
+            slice(10, 11, ColorSet::new(white, black).with(Attribute::Dim)),
+        ]);
+
+        //wide and combined characters count as chars like everywhere else.
+        let parsed = parse("世[fg=7]e\u{301}[/]").unwrap();
+        assert_eq!(parsed.colors, vec![slice(1, 3, ColorSet::new(Color::AnsiValue(7), black))]);
+        assert_eq!(parsed.get_color(0), None);
+        assert_eq!(parsed.get_color(2), Some(ColorSet::new(Color::AnsiValue(7), black)));
+    }
+
+    #[test]
+    fn styles() {
+        let mut markup = Markup::new();
+        markup.default = ColorSet::new(Color::Grey, Color::Black);
+        markup.style("warn", "fg=yellow bold").unwrap();
+        markup.style("loud", "warn underline bg=dark_red").unwrap();
+
+        let parsed = markup.parse("[loud]x[/][warn fg=green]y[/]").unwrap();
+
+        let loud = ColorSet::new(Color::Yellow, Color::DarkRed).with(Attribute::Bold).with(Attribute::Underlined);
+        assert_eq!(parsed.colors, vec![
+            slice(0, 1, loud),
+            slice(1, 2, ColorSet::new(Color::Green, Color::Black).with(Attribute::Bold)),
+        ]);
+
+        assert_eq!(markup.style("bad", "warn shiny"), Err(MarkupError::UnknownStyle("shiny".to_string())));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("a[/]").unwrap_err(), MarkupError::UnexpectedClose(1));
+        assert_eq!(parse("ab[bold").unwrap_err(), MarkupError::UnterminatedTag(2));
+        assert_eq!(parse("[fg=#12345z]a[/]").unwrap_err(), MarkupError::BadColor("#12345z".to_string()));
+        assert_eq!(parse("[fg=purple]a[/]").unwrap_err(), MarkupError::BadColor("purple".to_string()));
+        assert_eq!(parse("[[bold]").unwrap().string, "[bold]");
+    }
+
+    #[test]
+    fn round_trip() {
+        let string = ColorString {
+            string: "ok [fail] ok".to_string(),
+            colors: vec![
+                slice(0, 2, ColorSet::new(Color::AnsiValue(200), Color::Reset)),
+                slice(3, 9, ColorSet::new(Color::DarkCyan, Color::Rgb{r: 1, g: 2, b: 255}).with(Attribute::CrossedOut).with(Attribute::Italic)),
+            ],
+        };
+
+        let markup = to_markup(&string);
+        assert_eq!(markup, "[fg=200 bg=reset]ok[/] [fg=dark_cyan bg=#0102ff italic strike][[fail][/] ok");
+        assert_eq!(parse(&markup).unwrap(), string);
+    }
+
+    #[test]
+    fn macro_escapes() {
+        let name = "[x]";
+        let parsed = markup!("[bold]{:>5}[/] {:.1}", name, 2.26).unwrap();
+
+        assert_eq!(parsed.string, "  [x] 2.3");
+        assert_eq!(parsed.colors.len(), 1);
+        assert_eq!((parsed.colors[0].start, parsed.colors[0].end), (0, 5));
+
+        assert!(markup!("[bogus]{}", 1).is_err());
+    }
+
+    #[test]
+    fn inline_captures() {
+        for fmt in ["{name}", "[bold]{_x:>3}[/]", "{é}"] {
+            assert!(captures_inline(fmt), "{}", fmt);
+        }
+
+        for fmt in ["{}", "{0:>5}", "{:.1}", "{{name}}", "[bold]{{[/]", "{"] {
+            assert!(!captures_inline(fmt), "{}", fmt);
+        }
+    }
+}
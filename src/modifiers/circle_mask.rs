@@ -71,4 +71,34 @@ fn calc_dis(pos: Coord, center: Coord) -> i32 {
     let rel_x = pos.x - center.x;
     let rel_y = pos.y - center.y;
     (rel_x * rel_x) + (rel_y * rel_y)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::frame_types::{fill, with_modifier};
+
+    #[test]
+    fn even_and_odd() {
+        for (name, size) in [("circle_mask_even", Coord{x: 8, y: 8}), ("circle_mask_odd", Coord{x: 9, y: 9})] {
+            let mut buf = ScreenBuf::new(size);
+
+            let circle = with_modifier::new(fill::new(Pixel::new('#', Color::White, Color::Black)), new(false));
+            circle.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+
+            assert_snapshot(name, &buf.buffer);
+        }
+    }
+
+    #[test]
+    fn inverted() {
+        let size = Coord{x: 8, y: 8};
+        let mut buf = ScreenBuf::new(size);
+
+        let circle = with_modifier::new(fill::new(Pixel::new('#', Color::White, Color::Black)), new(true));
+        circle.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, size);
+
+        assert_snapshot("circle_mask_inverted", &buf.buffer);
+    }
+}
@@ -68,6 +68,7 @@ impl CraftPosition {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PosData {
     pub pos: Coord,
     pub size: Coord,
@@ -79,6 +80,77 @@ pub struct PosData {
     pub enabled: bool,
 }
 
+impl PosData {
+    /// The size the frame takes up on the screen, the size with x and y swapped when rotated.
+    pub fn screen_size(&self) -> Coord {
+        if self.rot { rot_coord(self.size) }
+               else { self.size }
+    }
+
+    /// Sets the size so the frame takes up the given size on the screen.
+    pub fn set_screen_size(&mut self, size: Coord) {
+        self.size = if self.rot { rot_coord(size) }
+                           else { size };
+    }
+
+    /// Where a frame coordinate ends up relative to whatever the position is placed in.
+    pub fn place(&self, index: Coord) -> Coord {
+        self.translate_coord(index) + self.pos
+    }
+
+    /// The frame coordinate that ends up on point, the opposite of place.
+    pub fn local(&self, point: Coord) -> Coord {
+        let temp = point - self.pos;
+        let temp = if self.rot { rot_coord(temp) }
+                          else { temp };
+
+        Coord {
+            x: if self.xflip { self.size.x - 1 - temp.x }
+                        else { temp.x },
+            y: if self.yflip { self.size.y - 1 - temp.y }
+                        else { temp.y },
+        }
+    }
+
+    /// Turns the position of a child placed inside this one into a single position relative to what this one is in.
+    /// Flips and rotations of both are combined, and the child is only enabled if both are.
+    pub fn compose(&self, child: &PosData) -> PosData {
+        let origin = self.place(child.place(Coord{x: 0, y: 0}));
+        let right  = self.place(child.place(Coord{x: 1, y: 0})) - origin;
+        let down   = self.place(child.place(Coord{x: 0, y: 1})) - origin;
+
+        //a rotated frame has its x going along the screens y.
+        let rot = right.y != 0;
+        let (xflip, yflip) = if rot { (right.y < 0, down.x < 0) }
+                                else { (right.x < 0, down.y < 0) };
+
+        let mut data = PosData {
+            pos:     Coord{x: 0, y: 0},
+            rot,
+            xflip,
+            yflip,
+            enabled: self.enabled && child.enabled,
+            ..*child
+        };
+
+        data.pos = origin - data.place(Coord{x: 0, y: 0});
+        data
+    }
+
+    /// Turns a frame coordinate into one relative to pos.
+    fn translate_coord(&self, index: Coord) -> Coord {
+        let temp = Coord {
+            x: if self.xflip { self.size.x - 1 - index.x }
+                        else { index.x },
+            y: if self.yflip { self.size.y - 1 - index.y }
+                        else { index.y },
+        };
+
+        if self.rot { rot_coord(temp) }
+               else { temp }
+    }
+}
+
 /// An Object holds a reference to a frame and all of the positional data for how it is drawn onto the screen.
 /// ## Functions
 /// - new
@@ -100,20 +172,32 @@ pub struct IPosition {
 
 impl IModifier for IPosition {
     fn modify(&mut self, pos_pixel: &mut PosPixel) {
-        pos_pixel.pos = self.translate_coord(pos_pixel.pos, self.true_size) + self.data.pos;
+        pos_pixel.pos = self.data.place(pos_pixel.pos);
     }
 
-    fn mod_position(&mut self, cur_size: Coord, _cur_offset: Coord) -> PositionModifier {
-        let end   = self.data.pos + self.match_rot(self.data.size);
+    fn mod_position(&mut self, cur_size: Coord, cur_offset: Coord) -> PositionModifier {
+        let end = self.data.pos + self.match_rot(self.data.size);
 
-        let new_start = take_bigger(self.data.pos, Coord{x: 0, y: 0});
-        let new_end   = take_smaller(end, self.match_rot(cur_size));
+        //the part of the area that is visible relative to pos.
+        let start = take_bigger(self.data.pos, cur_offset) - self.data.pos;
+        let end   = take_smaller(end, cur_offset + cur_size) - self.data.pos;
 
-        self.true_size = new_end - new_start;
+        //turn the visible part back into frame coordinates.
+        let mut start = self.match_rot(start);
+        let mut end   = self.match_rot(end);
+
+        if self.data.xflip {
+            (start.x, end.x) = (self.data.size.x - end.x, self.data.size.x - start.x);
+        }
+        if self.data.yflip {
+            (start.y, end.y) = (self.data.size.y - end.y, self.data.size.y - start.y);
+        }
+
+        self.true_size = take_bigger(end - start, Coord{x: 0, y: 0});
 
         PositionModifier {
             size:   Some(self.true_size),
-            offset: Some(self.calc_offset()),
+            offset: Some(start),
         }
     }
 
@@ -151,6 +235,15 @@ impl IPosition {
         }
     }
 
+    /// Create a new Object from already worked out positional data, without a size update.
+    pub fn with_data(data: PosData) -> Self {
+        IPosition {
+            data,
+            true_size:   Coord{x:0, y:0},
+            size_update: None,
+        }
+    }
+
     pub fn set_update<T: SizeUpdate + Sized + 'static>(&mut self, update: T) {
         self.size_update = Some(Box::new(update))
     }
@@ -161,7 +254,7 @@ impl IPosition {
 
     /// Sets the Objects Center position of the object to the provided coord.
     pub fn set_center(&mut self, new: Coord) {
-        self.data.pos = new - (self.data.size / Coord{ x: 2, y: 2 });
+        self.data.pos = new - (self.match_rot(self.data.size) / Coord{ x: 2, y: 2 });
     }
 
     /// Flips the frame over the x axis.
@@ -202,28 +295,10 @@ impl IPosition {
         self.data.offset %= self.data.frame_size * Coord{ x: 2, y: 2 };
     }
 
-    fn calc_offset(&self) -> Coord {
-        Coord {
-            x: min(self.data.pos.x, 0) * -1,
-            y: min(self.data.pos.y, 0) * -1,
-        }
-    }
-
     fn match_rot(&self, c: Coord) -> Coord {
         if self.data.rot { rot_coord(c) }
                     else { c }
     }
-
-    fn translate_coord(&self, index: Coord, size: Coord) -> Coord {
-        let temp = Coord {
-            x: if self.data.xflip { size.x - index.x }
-                             else { index.x },
-            y: if self.data.yflip { size.y - index.y }
-                             else { index.y },
-        };
-
-        self.match_rot(temp)
-    }
 }
 
 fn take_smaller(c1: Coord, c2: Coord) -> Coord {
@@ -425,4 +500,131 @@ mod tests {
             assert_eq!(buf.buffer.get_flat(i), *x)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn flips() {
+        let b = Pixel::new('B', Rgb{r: 0, g: 0, b: 0}, Rgb{r: 0, g: 0, b: 0});
+        let r = Pixel::new('R', Rgb{r: 255, g: 0, b: 0}, Rgb{r: 0, g: 0, b: 0});
+        let w = Pixel::new('W', Rgb{r: 255, g: 255, b: 255}, Rgb{r: 0, g: 0, b: 0});
+        let c = Pixel::Clear;
+
+        //each flip mirrors the frame in place without moving it.
+        let xflip = vec![
+            b,w,w,w,r,r,r,b,c,
+            w,b,b,w,r,b,b,r,c,
+            w,b,b,w,r,b,b,r,c,
+            w,w,w,w,r,r,r,r,c,
+            w,b,w,w,r,r,b,r,c,
+            w,w,b,b,b,b,r,r,c,
+            b,w,w,w,r,r,r,b,c,
+            c,c,c,c,c,c,c,c,c,
+        ];
+
+        let yflip = vec![
+            b,r,r,r,w,w,w,b,c,
+            r,r,b,b,b,b,w,w,c,
+            r,b,r,r,w,w,b,w,c,
+            r,r,r,r,w,w,w,w,c,
+            r,b,b,r,w,b,b,w,c,
+            r,b,b,r,w,b,b,w,c,
+            b,r,r,r,w,w,w,b,c,
+            c,c,c,c,c,c,c,c,c,
+        ];
+
+        for (pos, expected) in [(craft().xflip(true), xflip), (craft().yflip(true), yflip)] {
+            let mut buf = ScreenBuf::new(Coord{x: 9, y: 8});
+
+            let smile = with_modifier::new(test_smile(), pos.size(Coord{x: 8, y: 7}).done());
+            smile.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 9, y: 8});
+
+            print_buffer(&buf);
+
+            for (i, x) in expected.iter().enumerate() {
+                assert_eq!(buf.buffer.get_flat(i), *x)
+            }
+        }
+    }
+
+    #[test]
+    fn rotated_clipping() {
+        let mut buf = ScreenBuf::new(Coord{x: 6, y: 6});
+
+        //rotated the frame is 7 wide and 8 tall, so the clipped part has to be worked out from the rotated size.
+        let pos = craft().size(Coord{x: 8, y: 7}).pos(Coord{x: -2, y: -1}).done();
+        pos.borrow_mut().rot_cw();
+
+        let smile = with_modifier::new(test_smile(), pos);
+        smile.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 6, y: 6});
+
+        let b = Pixel::new('B', Rgb{r: 0, g: 0, b: 0}, Rgb{r: 0, g: 0, b: 0});
+        let r = Pixel::new('R', Rgb{r: 255, g: 0, b: 0}, Rgb{r: 0, g: 0, b: 0});
+        let w = Pixel::new('W', Rgb{r: 255, g: 255, b: 255}, Rgb{r: 0, g: 0, b: 0});
+        let c = Pixel::Clear;
+
+        let expected = vec![
+            b,r,b,b,r,c,
+            r,r,b,b,r,c,
+            r,r,r,r,r,c,
+            w,w,w,w,w,c,
+            w,w,b,b,w,c,
+            b,w,b,b,w,c,
+        ];
+
+        print_buffer(&buf);
+
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(buf.buffer.get_flat(i), *x)
+        }
+    }
+
+    #[test]
+    fn center() {
+        let pos = craft().size(Coord{x: 8, y: 7}).done();
+
+        pos.borrow_mut().set_center(Coord{x: 10, y: 10});
+        assert_eq!(pos.borrow().data.pos, Coord{x: 6, y: 7});
+
+        //rotated frames are centered on their rotated size.
+        pos.borrow_mut().rot_cw();
+        pos.borrow_mut().set_center(Coord{x: 10, y: 10});
+        assert_eq!(pos.borrow().data.pos, Coord{x: 7, y: 6});
+    }
+
+    type Turn = fn(&mut IPosition);
+
+    #[test]
+    fn rotations() {
+        let turns: Vec<(&str, Turn)> = vec![
+            ("position_flipx",   IPosition::flipx),
+            ("position_flipy",   IPosition::flipy),
+            ("position_rot_cw",  IPosition::rot_cw),
+            ("position_rot_ccw", IPosition::rot_ccw),
+            ("position_rot_180", IPosition::rot_180),
+        ];
+
+        for (name, turn) in turns {
+            let mut buf = ScreenBuf::new(Coord{x: 10, y: 10});
+
+            let pos = craft().size(Coord{x: 8, y: 7}).pos(Coord{x: 1, y: 1}).done();
+            turn(&mut pos.borrow_mut());
+
+            let smile = with_modifier::new(test_smile(), pos);
+            smile.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 10, y: 10});
+
+            assert_snapshot(name, &buf.buffer);
+        }
+    }
+
+    #[test]
+    fn rotated_out_of_range() {
+        let mut buf = ScreenBuf::new(Coord{x: 6, y: 6});
+
+        let pos = craft().size(Coord{x: 8, y: 7}).pos(Coord{x: -2, y: -1}).done();
+        pos.borrow_mut().rot_cw();
+
+        let smile = with_modifier::new(test_smile(), pos);
+        smile.borrow().get_draw_data(&mut buf, Coord{x: 0, y: 0}, Coord{x: 6, y: 6});
+
+        assert_snapshot("position_rot_cw_clipped", &buf.buffer);
+    }
+}
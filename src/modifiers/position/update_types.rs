@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use super::{SizeUpdate, PosData};
+use super::{SizeUpdate, PosData, Position};
 
 pub struct NoUpdate {}
 
@@ -13,4 +13,276 @@ impl SizeUpdate for MatchSize {
     fn size_update(&mut self, pos: &mut PosData, new_size: Coord){
         pos.size = new_size;
     }
-}
\ No newline at end of file
+}
+
+/// One of the nine points on the edges and middle of an area.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Point {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Point {
+    /// Where the point is in an area of the given size.
+    pub fn of(&self, size: Coord) -> Coord {
+        let (x, y) = self.parts();
+
+        Coord {
+            x: size.x * x / 2,
+            y: size.y * y / 2,
+        }
+    }
+
+    /// 0 is the left or top, 1 the middle and 2 the right or bottom.
+    fn parts(&self) -> (i32, i32) {
+        match self {
+            Point::TopLeft     => (0, 0),
+            Point::Top         => (1, 0),
+            Point::TopRight    => (2, 0),
+            Point::Left        => (0, 1),
+            Point::Center      => (1, 1),
+            Point::Right       => (2, 1),
+            Point::BottomLeft  => (0, 2),
+            Point::Bottom      => (1, 2),
+            Point::BottomRight => (2, 2),
+        }
+    }
+
+    /// Margins push away from the edge the point is on, for the middle they just move it right or down.
+    fn margin(&self, margin: Coord) -> Coord {
+        let (x, y) = self.parts();
+
+        Coord {
+            x: if x == 2 { -margin.x } else { margin.x },
+            y: if y == 2 { -margin.y } else { margin.y },
+        }
+    }
+}
+
+/// Puts the same point of the frame and the screen on top of each other, Point::Bottom keeps the frame centered along the bottom edge.
+pub struct Anchor {
+    pub point:  Point,
+    pub margin: Coord,
+}
+
+impl Anchor {
+    pub fn new(point: Point) -> Self {
+        Anchor {
+            point,
+            margin: Coord{x: 0, y: 0},
+        }
+    }
+
+    pub fn margin(mut self, margin: Coord) -> Self {
+        self.margin = margin;
+        self
+    }
+}
+
+impl SizeUpdate for Anchor {
+    fn size_update(&mut self, pos: &mut PosData, new_size: Coord) {
+        pos.pos = self.point.of(new_size) - self.point.of(pos.screen_size()) + self.point.margin(self.margin);
+    }
+}
+
+/// Sizes and places the frame by percentages of the screen, 0 to 100.
+/// The position is where point of the frame goes, the top left unless set with at.
+pub struct Percent {
+    pub size:  Option<Coord>,
+    pub pos:   Option<Coord>,
+    pub point: Point,
+}
+
+impl Percent {
+    pub fn size(x: i32, y: i32) -> Self {
+        Percent {
+            size:  Some(Coord{x, y}),
+            pos:   None,
+            point: Point::TopLeft,
+        }
+    }
+
+    pub fn pos(x: i32, y: i32) -> Self {
+        Percent {
+            size:  None,
+            pos:   Some(Coord{x, y}),
+            point: Point::TopLeft,
+        }
+    }
+
+    pub fn and_pos(mut self, x: i32, y: i32) -> Self {
+        self.pos = Some(Coord{x, y});
+        self
+    }
+
+    pub fn at(mut self, point: Point) -> Self {
+        self.point = point;
+        self
+    }
+}
+
+impl SizeUpdate for Percent {
+    fn size_update(&mut self, pos: &mut PosData, new_size: Coord) {
+        if let Some(size) = self.size {
+            pos.set_screen_size(new_size * size / Coord{x: 100, y: 100});
+        }
+
+        if let Some(percent) = self.pos {
+            pos.pos = new_size * percent / Coord{x: 100, y: 100} - self.point.of(pos.screen_size());
+        }
+    }
+}
+
+/// Makes the frame as big as it can be on the screen while keeping the x to y ratio of aspect.
+pub struct FitAspect {
+    pub aspect: Coord,
+}
+
+impl SizeUpdate for FitAspect {
+    fn size_update(&mut self, pos: &mut PosData, new_size: Coord) {
+        if self.aspect.x <= 0 || self.aspect.y <= 0 { return }
+
+        //try using the full width, if that is too tall use the full height.
+        let height = new_size.x * self.aspect.y / self.aspect.x;
+
+        let size = if height <= new_size.y { Coord{x: new_size.x, y: height} }
+                                      else { Coord{x: new_size.y * self.aspect.x / self.aspect.y, y: new_size.y} };
+
+        pos.set_screen_size(size);
+    }
+}
+
+/// Keeps the size between min and max, meant to come after an update that sets the size in a Chain.
+pub struct Clamp {
+    pub min: Coord,
+    pub max: Coord,
+}
+
+impl SizeUpdate for Clamp {
+    fn size_update(&mut self, pos: &mut PosData, _new_size: Coord) {
+        let size = pos.screen_size();
+
+        pos.set_screen_size(Coord {
+            x: size.x.max(self.min.x).min(self.max.x),
+            y: size.y.max(self.min.y).min(self.max.y),
+        });
+    }
+}
+
+/// Places the frame relative to another position, point of this frame goes on target_point of the target moved by offset.
+/// The target has to be updated first, so it should be added to the layout before this, layouts update objects in the order they were added with parents before their children.
+pub struct RelativeTo {
+    pub target:       Position,
+    pub target_point: Point,
+    pub point:        Point,
+    pub offset:       Coord,
+}
+
+impl SizeUpdate for RelativeTo {
+    fn size_update(&mut self, pos: &mut PosData, _new_size: Coord) {
+        let target = self.target.borrow();
+        let anchor = target.data.pos + self.target_point.of(target.data.screen_size());
+
+        pos.pos = anchor - self.point.of(pos.screen_size()) + self.offset;
+    }
+}
+
+/// Runs each update in order, so sizes can be set before the position is worked out from them.
+pub struct Chain {
+    pub updates: Vec<Box<dyn SizeUpdate>>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Chain {
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn then<T: SizeUpdate + 'static>(mut self, update: T) -> Self {
+        self.updates.push(Box::new(update));
+        self
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizeUpdate for Chain {
+    fn size_update(&mut self, pos: &mut PosData, new_size: Coord) {
+        for update in &mut self.updates {
+            update.size_update(pos, new_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifiers::position::craft;
+
+    fn placed<T: SizeUpdate + 'static>(size: Coord, update: T) -> (Coord, Coord) {
+        let pos = craft().size(size).update(update).done();
+        pos.borrow_mut().update(Coord{x: 80, y: 24});
+
+        let data = &pos.borrow().data;
+        (data.pos, data.size)
+    }
+
+    #[test]
+    fn anchor() {
+        let size = Coord{x: 10, y: 4};
+
+        assert_eq!(placed(size, Anchor::new(Point::TopLeft)).0, Coord{x: 0, y: 0});
+        assert_eq!(placed(size, Anchor::new(Point::Center)).0, Coord{x: 35, y: 10});
+        assert_eq!(placed(size, Anchor::new(Point::Bottom)).0, Coord{x: 35, y: 20});
+        assert_eq!(placed(size, Anchor::new(Point::BottomRight).margin(Coord{x: 1, y: 2})).0, Coord{x: 69, y: 18});
+        assert_eq!(placed(size, Anchor::new(Point::Left).margin(Coord{x: 3, y: 0})).0, Coord{x: 3, y: 10});
+
+        //rotated frames take up their size the other way around.
+        let pos = craft().size(size).rot(true).update(Anchor::new(Point::BottomRight)).done();
+        pos.borrow_mut().update(Coord{x: 80, y: 24});
+        assert_eq!(pos.borrow().data.pos, Coord{x: 76, y: 14});
+    }
+
+    #[test]
+    fn percent_fit_and_clamp() {
+        let size = Coord{x: 10, y: 4};
+
+        assert_eq!(placed(size, Percent::size(50, 25).and_pos(50, 50).at(Point::Center)), (Coord{x: 20, y: 9}, Coord{x: 40, y: 6}));
+        assert_eq!(placed(size, FitAspect{aspect: Coord{x: 2, y: 1}}).1, Coord{x: 48, y: 24});
+        assert_eq!(placed(size, FitAspect{aspect: Coord{x: 10, y: 1}}).1, Coord{x: 80, y: 8});
+
+        let chain = Chain::new()
+            .then(Percent::size(100, 100))
+            .then(Clamp{min: Coord{x: 0, y: 0}, max: Coord{x: 60, y: 10}})
+            .then(Anchor::new(Point::Top));
+
+        assert_eq!(placed(size, chain), (Coord{x: 10, y: 0}, Coord{x: 60, y: 10}));
+    }
+
+    #[test]
+    fn relative_to() {
+        let target = craft().size(Coord{x: 10, y: 4}).update(Anchor::new(Point::Center)).done();
+        target.borrow_mut().update(Coord{x: 80, y: 24});
+
+        let update = RelativeTo {
+            target:       target.clone(),
+            target_point: Point::TopRight,
+            point:        Point::BottomLeft,
+            offset:       Coord{x: 1, y: 0},
+        };
+
+        assert_eq!(placed(Coord{x: 4, y: 2}, update).0, Coord{x: 46, y: 8});
+    }
+}
@@ -1,15 +1,110 @@
-pub use crossterm::style::Color;
+pub use crossterm::style::{Color, Attribute, Attributes};
 pub use crossterm::event::{KeyEvent, MouseEvent};
 
 pub use std::{
     rc::Rc, cell::RefCell
 };
 
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 mod screenbuf;
 pub use screenbuf::*;
 
 pub type Coord = coord::Coord<i32>;
 
+///Number of cells the character takes up on the screen, CJK and most emoji take up two.
+pub fn char_width(c: char) -> i32 {
+    if UnicodeWidthChar::width(c) == Some(2) { 2 }
+                                       else { 1 }
+}
+
+///Number of cells a character followed by marks takes up, never more than two.
+pub fn marked_width(c: char, marks: Marks) -> i32 {
+    if marks.is_empty() { return char_width(c) }
+
+    (char_width(c) + marks.as_str().width() as i32).min(2)
+}
+
+///Splits a grapheme cluster into the character a pixel holds and the marks drawn after it, None if it takes up no space.
+///Clusters are composed first, so an accented letter with a single character form doesn't need any marks.
+///"\r\n" is a new line and other control characters are returned alone for the caller to deal with.
+pub fn split_cluster(cluster: &str) -> Option<(char, Marks)> {
+    if cluster == "\r\n" { return Some(('\n', Marks::NONE)) }
+
+    let first = cluster.chars().next()?;
+    if first.is_control() { return Some((first, Marks::NONE)) }
+
+    if cluster.width() == 0 { return None }
+
+    let composed: String = cluster.nfc().collect();
+    let mut chars = composed.chars();
+    let c = chars.next()?;
+
+    Some((c, Marks::new(chars.as_str())))
+}
+
+///Every set of marks used so far, so each is only stored once.
+#[derive(Default)]
+struct MarksTable {
+    list: Vec<&'static str>,
+    ids:  HashMap<&'static str, u32>,
+}
+
+fn marks_table() -> MutexGuard<'static, MarksTable> {
+    static TABLE: OnceLock<Mutex<MarksTable>> = OnceLock::new();
+
+    TABLE.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The rest of a grapheme cluster drawn after a pixel's character, like combining marks or the other parts of a joined emoji.
+/// Each different set of marks is stored once for the whole program so pixels stay small and can still be copied.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Marks(u32);
+
+impl Marks {
+    pub const NONE: Marks = Marks(0);
+
+    pub fn new(marks: &str) -> Marks {
+        if marks.is_empty() { return Marks::NONE }
+
+        let mut table = marks_table();
+
+        if let Some(id) = table.ids.get(marks) {
+            return Marks(*id)
+        }
+
+        let marks: &'static str = Box::leak(marks.into());
+        table.list.push(marks);
+
+        let id = table.list.len() as u32;
+        table.ids.insert(marks, id);
+
+        Marks(id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self.0 {
+            0  => "",
+            id => marks_table().list[id as usize - 1],
+        }
+    }
+}
+
+impl fmt::Debug for Marks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Marks({:?})", self.as_str())
+    }
+}
+
 pub fn wrap<T>(x: T) -> Rc<RefCell<T>> {
     Rc::new(RefCell::new(x))
 }
@@ -66,43 +161,149 @@ pub trait IModifier {
 
 pub type Modifier = Rc<RefCell<dyn IModifier>>;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ColorSet {
     pub fg: Color,
     pub bg: Color,
+    pub attributes: Attributes,
+}
+
+impl ColorSet {
+    pub fn new(fg: Color, bg: Color) -> ColorSet {
+        ColorSet {
+            fg,
+            bg,
+            attributes: Attributes::default(),
+        }
+    }
+
+    ///Returns the color set with the attribute added.
+    pub fn with(mut self, attribute: Attribute) -> ColorSet {
+        self.attributes.set(attribute);
+        self
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct PixelData {
     pub character: char,
+    ///Drawn after character to make up the whole grapheme cluster, empty for most pixels.
+    pub marks: Marks,
     pub fg: Color,
     pub bg: Color,
+    pub attributes: Attributes,
 }
 
 impl PixelData {
     pub fn new(character: char, fg: Color, bg: Color) -> PixelData {
         PixelData {
             character,
+            marks: Marks::NONE,
             fg,
             bg,
+            attributes: Attributes::default(),
         }
     }
 
     pub fn new_color_set(character: char, colors: ColorSet) -> PixelData {
         PixelData {
             character,
+            marks: Marks::NONE,
             fg: colors.fg,
             bg: colors.bg,
+            attributes: colors.attributes,
         }
     }
 
+    ///A pixel holding a whole grapheme cluster, None if the cluster takes up no space.
+    pub fn cluster(cluster: &str, colors: ColorSet) -> Option<PixelData> {
+        let (character, marks) = split_cluster(cluster)?;
+
+        Some(PixelData { marks, ..PixelData::new_color_set(character, colors) })
+    }
+
+    ///Returns the pixel with its character replaced and no marks.
+    pub fn with_char(self, character: char) -> PixelData {
+        PixelData { character, marks: Marks::NONE, ..self }
+    }
+
     pub fn get_color_set(&self) -> ColorSet {
-        ColorSet { fg: self.fg, bg: self.bg }
+        ColorSet { fg: self.fg, bg: self.bg, attributes: self.attributes }
     }
 
     pub fn set_color_set(&mut self, colors: ColorSet) {
         self.fg = colors.fg;
         self.bg = colors.bg;
+        self.attributes = colors.attributes;
+    }
+
+    ///Number of cells the character and its marks take up on the screen, never more than two.
+    pub fn width(&self) -> i32 {
+        marked_width(self.character, self.marks)
+    }
+}
+
+///How a blended pixel changes one of the colors of whatever is under it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Channel {
+    ///Leaves the color under it as it is.
+    Keep,
+    ///Replaces the color under it.
+    Set(Color),
+    ///Mixes the color with the one under it, 255 is fully the new color.
+    Alpha(Color, u8),
+}
+
+impl Channel {
+    pub fn apply(&self, under: Color) -> Color {
+        match *self {
+            Channel::Keep            => under,
+            Channel::Set(color)      => color,
+            Channel::Alpha(color, a) => crate::color::blend(color, under, a),
+        }
+    }
+}
+
+///A pixel that is combined with what is already in the buffer instead of replacing it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlendData {
+    ///None keeps the character under it.
+    pub character: Option<char>,
+    pub fg: Channel,
+    pub bg: Channel,
+}
+
+impl BlendData {
+    ///Keeps everything, only changes the background.
+    pub fn tint(color: Color, alpha: u8) -> BlendData {
+        BlendData {
+            character: None,
+            fg: Channel::Keep,
+            bg: Channel::Alpha(color, alpha),
+        }
+    }
+
+    ///Keeps the character and darkens both colors, like a shadow falling on it.
+    pub fn shade(color: Color, alpha: u8) -> BlendData {
+        BlendData {
+            character: None,
+            fg: Channel::Alpha(color, alpha),
+            bg: Channel::Alpha(color, alpha),
+        }
+    }
+
+    ///Returns the pixel that results from putting this on top of under.
+    pub fn over(&self, under: PixelData) -> PixelData {
+        let under = match self.character {
+            Some(c) => under.with_char(c),
+            None    => under,
+        };
+
+        PixelData {
+            fg: self.fg.apply(under.fg),
+            bg: self.bg.apply(under.bg),
+            ..under
+        }
     }
 }
 
@@ -110,44 +311,41 @@ impl PixelData {
 pub enum Pixel {
     Clear,
     Opaque(PixelData),
+    ///The right half of a wide character, the buffer puts these after wide characters itself so setting one does nothing.
+    Continuation,
+    ///Combined with what is already in the buffer when it is set.
+    Blend(BlendData),
 }
 
 impl Pixel {
     pub fn new(character: char, fg: Color, bg: Color) -> Pixel {
-        Self::Opaque(
-            PixelData {
-                character,
-                fg,
-                bg,
-            }
-        )
+        Self::Opaque(PixelData::new(character, fg, bg))
     }
 
     pub fn new_color_set(character: char, colors: ColorSet) -> Pixel {
-        Self::Opaque(
-            PixelData {
-                character,
-                fg: colors.fg,
-                bg: colors.bg,
-            }
-        )
+        Self::Opaque(PixelData::new_color_set(character, colors))
+    }
+
+    pub fn new_blend(character: Option<char>, fg: Channel, bg: Channel) -> Pixel {
+        Self::Blend(BlendData { character, fg, bg })
     }
 
     pub fn as_ref(&self) -> Option<&PixelData> {
         match *self {
             Self::Opaque(ref x) => Some(x),
-            Self::Clear => None,
+            _ => None,
         }
     }
 
     pub fn as_mut(&mut self) -> Option<&mut PixelData> {
         match *self {
             Self::Opaque(ref mut x) => Some(x),
-            Self::Clear => None,
+            _ => None,
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Input {
     KeyBoard(KeyEvent),
     Mouse(MouseEvent),
@@ -16,6 +16,8 @@ pub struct ScreenBuf {
     pub buffer: Vec2D,
     modifiers:  Vec<Modifier>,
     pos:        Vec<Pos>,
+    ///the point being probed and if anything was drawn on it since the last check.
+    probe:      Option<(Coord, bool)>,
 }
 
 impl ScreenBuf {
@@ -24,6 +26,23 @@ impl ScreenBuf {
             buffer:    Vec2D::new(size),
             modifiers: Vec::new(),
             pos:       Vec::new(),
+            probe:     None,
+        }
+    }
+
+    /// A screen buffer that doesn't keep anything drawn to it, it only notes when a pixel ends up on point.
+    pub fn new_probe(size: Coord, point: Coord) -> Self {
+        ScreenBuf {
+            probe: Some((point, false)),
+            ..ScreenBuf::new(size)
+        }
+    }
+
+    /// Returns true if something was drawn on the probed point since the last call.
+    pub fn take_probe(&mut self) -> bool {
+        match &mut self.probe {
+            Some((_, hit)) => std::mem::replace(hit, false),
+            None => false,
         }
     }
 
@@ -31,8 +50,13 @@ impl ScreenBuf {
         self.buffer.set_size(size);
     }
 
+    /// Clears everything drawn so the next frame starts from nothing.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
     pub fn set(&mut self, pos: Coord, pixel: Pixel) {
-        if pixel == Pixel::Clear { return }
+        if pixel == Pixel::Clear || pixel == Pixel::Continuation { return }
 
         let mut pos_pixel = PosPixel {
             pos: pos,
@@ -46,6 +70,11 @@ impl ScreenBuf {
             if pos_pixel.pixel == Pixel::Clear { return }
         }
 
+        if let Some((point, hit)) = &mut self.probe {
+            *hit |= pos_pixel.pos == *point;
+            return
+        }
+
         self.buffer.set(pos_pixel.pos, pos_pixel.pixel)
     }
 
@@ -78,7 +107,17 @@ impl ScreenBuf {
         self.modifiers.pop();
     }
 
-    pub fn use_modifier_on(&mut self, modifier: Modifier, frame: &Frame, mut offset: Coord, mut size: Coord) {
+    pub fn use_modifier_on(&mut self, modifier: Modifier, frame: &Frame, offset: Coord, size: Coord) {
+        self.use_modifier_with(modifier, offset, size, |screenbuf, offset, size| {
+            if size.x > 0 && size.y > 0 {
+                frame.borrow().get_draw_data(screenbuf, offset, size);
+            }
+        });
+    }
+
+    /// Like use_modifier_on but runs draw while the modifier is applied, so more than one frame can be drawn through it.
+    /// draw is given the offset and size the modifier wants, even if the size is empty.
+    pub fn use_modifier_with<F: FnOnce(&mut ScreenBuf, Coord, Coord)>(&mut self, modifier: Modifier, mut offset: Coord, mut size: Coord, draw: F) {
         {
             let mut modifier = modifier.borrow_mut();
             modifier.init(&self);
@@ -88,9 +127,7 @@ impl ScreenBuf {
 
         self.add_mod(modifier);
 
-        if size.x > 0 && size.y > 0 {
-            frame.borrow().get_draw_data(self, offset, size);
-        }
+        draw(self, offset, size);
 
         self.remove_mod();
     }
@@ -151,4 +188,26 @@ mod tests {
             assert_eq!(expected[i], x)
         }
     }
+
+    #[test]
+    fn blend() {
+        let mut buf = ScreenBuf::new(Coord{x: 4, y: 1});
+        let white = Color::Rgb{r: 255, g: 255, b: 255};
+
+        buf.set(Coord{x: 0, y: 0}, Pixel::new('a', white, Color::Blue));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new('b', white, Color::Rgb{r: 200, g: 100, b: 0}));
+        buf.set(Coord{x: 2, y: 0}, Pixel::new('世', white, Color::Red));
+
+        buf.set(Coord{x: 0, y: 0}, Pixel::Blend(BlendData::tint(Color::Black, 255)));
+        buf.set(Coord{x: 1, y: 0}, Pixel::Blend(BlendData::shade(Color::Black, 128)));
+        buf.set(Coord{x: 2, y: 0}, Pixel::new_blend(Some('x'), Channel::Keep, Channel::Set(Color::Green)));
+        buf.set(Coord{x: 3, y: 0}, Pixel::Blend(BlendData::tint(Color::Green, 255)));
+
+        assert_eq!(buf.buffer.get(Coord{x: 0, y: 0}), Pixel::new('a', white, Color::Rgb{r: 0, g: 0, b: 0}));
+        assert_eq!(buf.buffer.get(Coord{x: 1, y: 0}), Pixel::new('b', Color::Rgb{r: 127, g: 127, b: 127}, Color::Rgb{r: 100, g: 50, b: 0}));
+        //replacing the character of a wide one blanks its right half.
+        assert_eq!(buf.buffer.get(Coord{x: 2, y: 0}), Pixel::new('x', white, Color::Green));
+        //blending onto the right half of the old wide character.
+        assert_eq!(buf.buffer.get(Coord{x: 3, y: 0}), Pixel::new(' ', white, Color::Rgb{r: 0, g: 255, b: 0}));
+    }
 }
\ No newline at end of file
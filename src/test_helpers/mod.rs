@@ -1,13 +1,25 @@
 use crate::prelude::*;
 
+mod test_backend;
+pub use test_backend::*;
+
+mod snapshot;
+pub use snapshot::*;
+
 pub fn print_pixel(pix: Pixel) {
     match pix {
         Pixel::Opaque(x) => {
-            print!("[{}]", x.character)
+            print!("[{}{}]", x.character, x.marks.as_str())
         }
         Pixel::Clear => {
             print!("[ ]")
         }
+        Pixel::Continuation => {
+            print!("[~]")
+        }
+        Pixel::Blend(x) => {
+            print!("[{}]", x.character.unwrap_or('*'))
+        }
     }
 }
 
@@ -0,0 +1,339 @@
+use crate::prelude::*;
+use crate::Vec2D;
+use crate::manager::ATTRIBUTES;
+
+use std::path::PathBuf;
+use std::fs;
+
+///Setting this environment variable to anything replaces stored snapshots with the current output instead of comparing them.
+pub const BLESS_VAR: &str = "FRAMES_BLESS";
+
+///Grids are wrapped in this so trailing spaces survive editors.
+const BORDER: char = '|';
+
+///Shown in the color grids for clear pixels.
+const CLEAR: char = '.';
+
+///Shown in the color grids for the right half of wide characters, which have nothing in the chars grid.
+const CONTINUATION: char = '~';
+
+///Turns a buffer into a stable text format.
+///
+/// ```text
+/// size: 3x1
+/// chars:
+/// |ab |
+/// fg:
+/// |aa.|
+/// bg:
+/// |bb.|
+/// colors:
+/// a: Red
+/// b: Black
+/// ```
+///
+///The fg and bg grids use the keys listed under colors, clear pixels are a space in chars and a '.' in the color grids.
+///If any pixel has attributes they are added the same way in an attributes grid with the keys listed under styles.
+///The right halves of wide characters are a '~' in the other grids and are skipped in chars, so the rows still line up.
+pub fn snapshot_string(buf: &Vec2D) -> String {
+    let size = buf.size();
+    let mut palette: Vec<String> = Vec::new();
+    let mut styles: Vec<String> = Vec::new();
+
+    let mut chars = String::new();
+    let mut fg = String::new();
+    let mut bg = String::new();
+    let mut attributes = String::new();
+
+    for y in 0..size.y {
+        for grid in [&mut chars, &mut fg, &mut bg, &mut attributes] {
+            grid.push(BORDER);
+        }
+
+        for x in 0..size.x {
+            match buf.get(Coord{x, y}) {
+                Pixel::Opaque(data) => {
+                    chars.push(data.character);
+                    chars.push_str(data.marks.as_str());
+                    fg.push(palette_key(&mut palette, format!("{:?}", data.fg)));
+                    bg.push(palette_key(&mut palette, format!("{:?}", data.bg)));
+
+                    if data.attributes.is_empty() {
+                        attributes.push(CLEAR);
+                    }
+                    else {
+                        attributes.push(palette_key(&mut styles, attribute_names(data.attributes)));
+                    }
+                }
+                Pixel::Clear => {
+                    chars.push(' ');
+                    fg.push(CLEAR);
+                    bg.push(CLEAR);
+                    attributes.push(CLEAR);
+                }
+                Pixel::Continuation => {
+                    fg.push(CONTINUATION);
+                    bg.push(CONTINUATION);
+                    attributes.push(CONTINUATION);
+                }
+                Pixel::Blend(_) => unreachable!("blended pixels are resolved when they are set"),
+            }
+        }
+
+        for grid in [&mut chars, &mut fg, &mut bg, &mut attributes] {
+            grid.push(BORDER);
+            grid.push('\n');
+        }
+    }
+
+    let mut out = format!("size: {}x{}\nchars:\n{}fg:\n{}bg:\n{}colors:\n", size.x, size.y, chars, fg, bg);
+
+    for (i, color) in palette.iter().enumerate() {
+        out.push_str(&format!("{}: {}\n", key(i), color));
+    }
+
+    if !styles.is_empty() {
+        out.push_str(&format!("attributes:\n{}styles:\n", attributes));
+
+        for (i, style) in styles.iter().enumerate() {
+            out.push_str(&format!("{}: {}\n", key(i), style));
+        }
+    }
+
+    out
+}
+
+fn attribute_names(attributes: Attributes) -> String {
+    ATTRIBUTES.iter()
+        .filter(|(on, _)| attributes.has(*on))
+        .map(|(on, _)| format!("{:?}", on))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+///Compares the buffer to the snapshot stored in `snapshots/<name>.snap` in the crate being tested.
+///Snapshots are only written when FRAMES_BLESS is set, a missing snapshot fails the same as one that doesn't match.
+pub fn assert_snapshot(name: &str, buf: &Vec2D) {
+    let path = snapshot_path(name);
+    let found = snapshot_string(buf);
+
+    if std::env::var_os(BLESS_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &found).unwrap();
+        return
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) => panic!(
+            "snapshot {} could not be read ({}), run with {}=1 to store it.\nfound:\n{}",
+            path.display(), e, BLESS_VAR, found
+        ),
+    };
+
+    if expected == found { return }
+
+    panic!(
+        "snapshot {} does not match, run with {}=1 to replace it.\n{}\nexpected:\n{}\nfound:\n{}",
+        path.display(), BLESS_VAR, diff(&expected, &found), expected, found
+    );
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+
+    root.join("snapshots").join(format!("{}.snap", name))
+}
+
+fn key(index: usize) -> char {
+    const KEYS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    match KEYS.get(index) {
+        Some(k) => *k as char,
+        //past the simple keys just keep counting through unicode.
+        None => char::from_u32(0x100 + (index - KEYS.len()) as u32).unwrap(),
+    }
+}
+
+fn palette_key(palette: &mut Vec<String>, name: String) -> char {
+    match palette.iter().position(|x| *x == name) {
+        Some(i) => key(i),
+        None => {
+            palette.push(name);
+            key(palette.len() - 1)
+        }
+    }
+}
+
+///A cell read back out of a snapshot string, None for clear pixels.
+type Cell = Option<(char, String, String, String)>;
+
+struct Parsed {
+    size:  Coord,
+    cells: Vec<Cell>,
+}
+
+fn parse(snapshot: &str) -> Option<Parsed> {
+    let mut lines = snapshot.lines();
+
+    let (x, y) = lines.next()?.strip_prefix("size: ")?.split_once('x')?;
+    let size = Coord{x: x.parse().ok()?, y: y.parse().ok()?};
+
+    //chars has nothing for the right half of wide characters, so it can only be split up once the fg grid is known.
+    if lines.next()? != "chars:" { return None }
+    let char_lines: Vec<&str> = (0..size.y).map(|_| lines.next()).collect::<Option<_>>()?;
+
+    let mut grid = |title: &str| -> Option<Vec<Vec<char>>> {
+        if lines.next()? != title { return None }
+
+        (0..size.y).map(|_| {
+            let line = lines.next()?;
+            Some(line.chars().skip(1).take(size.x as usize).collect())
+        }).collect()
+    };
+
+    let fg = grid("fg:")?;
+
+    let chars: Vec<Vec<char>> = char_lines.iter().zip(fg.iter()).map(|(line, fg)| {
+        let mut line = line.chars().skip(1);
+        fg.iter().map(|f| if *f == CONTINUATION { CONTINUATION } else { line.next().unwrap_or(' ') }).collect()
+    }).collect();
+
+    let bg = grid("bg:")?;
+
+    if lines.next()? != "colors:" { return None }
+
+    fn legend(lines: &mut std::str::Lines, end: Option<&str>) -> Vec<(char, String)> {
+        let mut keys = Vec::new();
+        for line in lines.by_ref() {
+            if Some(line) == end { break }
+
+            if let Some((k, name)) = line.split_once(": ") {
+                keys.push((k.chars().next().unwrap_or(' '), name.to_string()));
+            }
+        }
+        keys
+    }
+
+    let palette = legend(&mut lines, Some("attributes:"));
+    let (attributes, styles) = if lines.clone().next().is_some() {
+        let mut grid = Vec::new();
+        for _ in 0..size.y {
+            grid.push(lines.next()?.chars().skip(1).take(size.x as usize).collect::<Vec<char>>());
+        }
+        if lines.next()? != "styles:" { return None }
+        (Some(grid), legend(&mut lines, None))
+    }
+    else {
+        (None, Vec::new())
+    };
+
+    let lookup = |keys: &Vec<(char, String)>, k: char| -> String {
+        keys.iter().find(|(x, _)| *x == k).map(|(_, c)| c.clone()).unwrap_or_else(|| format!("unknown key '{}'", k))
+    };
+
+    let mut cells = Vec::new();
+    for y in 0..size.y as usize {
+        for x in 0..size.x as usize {
+            let f = *fg.get(y)?.get(x)?;
+            if f == CLEAR {
+                cells.push(None);
+                continue
+            }
+            if f == CONTINUATION {
+                cells.push(Some((CONTINUATION, String::new(), String::new(), String::new())));
+                continue
+            }
+
+            let style = match &attributes {
+                Some(grid) if *grid.get(y)?.get(x)? != CLEAR => lookup(&styles, grid[y][x]),
+                _ => String::new(),
+            };
+
+            cells.push(Some((*chars.get(y)?.get(x)?, lookup(&palette, f), lookup(&palette, *bg.get(y)?.get(x)?), style)));
+        }
+    }
+
+    Some(Parsed { size, cells })
+}
+
+///Lists the cells that are different between the two snapshots.
+fn diff(expected: &str, found: &str) -> String {
+    const MAX_LINES: usize = 20;
+
+    let (expected, found) = match (parse(expected), parse(found)) {
+        (Some(e), Some(f)) => (e, f),
+        _ => return "stored snapshot could not be read.".to_string(),
+    };
+
+    if expected.size != found.size {
+        return format!("size expected {}x{} found {}x{}", expected.size.x, expected.size.y, found.size.x, found.size.y)
+    }
+
+    let show = |cell: &Cell| -> String {
+        match cell {
+            Some((c, fg, bg, style)) if style.is_empty() => format!("'{}' fg: {} bg: {}", c, fg, bg),
+            Some((c, fg, bg, style)) => format!("'{}' fg: {} bg: {} attributes: {}", c, fg, bg, style),
+            None => "clear".to_string(),
+        }
+    };
+
+    let mut out = String::new();
+    let mut count = 0;
+
+    for (i, (e, f)) in expected.cells.iter().zip(found.cells.iter()).enumerate() {
+        if e == f { continue }
+
+        if count < MAX_LINES {
+            let x = i as i32 % expected.size.x;
+            let y = i as i32 / expected.size.x;
+            out.push_str(&format!("({}, {}) expected {} found {}\n", x, y, show(e), show(f)));
+        }
+        count += 1;
+    }
+
+    if count > MAX_LINES {
+        out.push_str(&format!("... and {} more\n", count - MAX_LINES));
+    }
+
+    format!("{} cells differ\n{}", count, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_diff() {
+        let mut buf = Vec2D::new(Coord{x: 3, y: 1});
+        buf.set(Coord{x: 0, y: 0}, Pixel::new('a', Color::Red, Color::Black));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new('b', Color::Red, Color::Black));
+
+        let expected = snapshot_string(&buf);
+        assert_eq!(expected, "size: 3x1\nchars:\n|ab |\nfg:\n|aa.|\nbg:\n|bb.|\ncolors:\na: Red\nb: Black\n");
+
+        buf.set(Coord{x: 2, y: 0}, Pixel::new('c', Color::Black, Color::Red));
+
+        assert_eq!(
+            diff(&expected, &snapshot_string(&buf)),
+            "1 cells differ\n(2, 0) expected clear found 'c' fg: Black bg: Red\n"
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let mut buf = Vec2D::new(Coord{x: 2, y: 1});
+        buf.set(Coord{x: 0, y: 0}, Pixel::new_color_set('a', ColorSet::new(Color::Red, Color::Black).with(Attribute::Bold)));
+        buf.set(Coord{x: 1, y: 0}, Pixel::new('b', Color::Red, Color::Black));
+
+        let expected = snapshot_string(&buf);
+        assert_eq!(expected, "size: 2x1\nchars:\n|ab|\nfg:\n|aa|\nbg:\n|bb|\ncolors:\na: Red\nb: Black\nattributes:\n|a.|\nstyles:\na: Bold\n");
+
+        buf.set(Coord{x: 1, y: 0}, Pixel::new_color_set('b', ColorSet::new(Color::Red, Color::Black).with(Attribute::Bold).with(Attribute::Italic)));
+
+        assert_eq!(
+            diff(&expected, &snapshot_string(&buf)),
+            "1 cells differ\n(1, 0) expected 'b' fg: Red bg: Black found 'b' fg: Red bg: Black attributes: Bold Italic\n"
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use crate::prelude::*;
+use crate::{Vec2D, CoordIter};
+use crate::manager::{Backend, ATTRIBUTES};
+use crate::color::ColorDepth;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::io;
+
+use crossterm::{ErrorKind, event::Event};
+
+/// A backend that draws into a Vec2D in memory instead of a terminal, so managers can be used without a TTY.
+/// ## Functions
+/// - new
+///
+/// ## Methods
+/// - buffer
+/// - resize
+/// - push_input
+/// - push_event
+/// - alt_screen
+/// - cursor_shown
+/// - set_color_depth
+/// - assert_chars
+/// - assert_colors
+pub struct TestBackend {
+    buffer:       Vec2D,
+    cursor:       Coord,
+    fg:           Color,
+    bg:           Color,
+    attributes:   Attributes,
+    ///cell of the last printed character, which marks are added to.
+    printed:      Option<Coord>,
+    alt_screen:   bool,
+    cursor_shown: bool,
+    events:       VecDeque<Event>,
+    color_depth:  ColorDepth,
+}
+
+impl TestBackend {
+    pub fn new(size: Coord) -> Self {
+        TestBackend {
+            buffer:       Vec2D::new(size),
+            cursor:       Coord{x: 0, y: 0},
+            fg:           Color::Reset,
+            bg:           Color::Reset,
+            attributes:   Attributes::default(),
+            printed:      None,
+            alt_screen:   false,
+            cursor_shown: true,
+            events:       VecDeque::new(),
+            color_depth:  ColorDepth::TrueColor,
+        }
+    }
+
+    ///What is currently shown on the fake screen.
+    pub fn buffer(&self) -> &Vec2D {
+        &self.buffer
+    }
+
+    ///Changes the size of the fake screen, and queues the resize event a real terminal would send.
+    pub fn resize(&mut self, size: Coord) {
+        self.buffer = Vec2D::new(size);
+        self.events.push_back(Event::Resize(size.x as u16, size.y as u16));
+    }
+
+    ///Queues an input to be returned by the managers input functions.
+    pub fn push_input(&mut self, input: Input) {
+        self.events.push_back(
+            match input {
+                Input::KeyBoard(e) => Event::Key(e),
+                Input::Mouse(e)    => Event::Mouse(e),
+                Input::FocusGained => Event::FocusGained,
+                Input::FocusLost   => Event::FocusLost,
+                Input::Paste(s)    => Event::Paste(s),
+            }
+        );
+    }
+
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    pub fn alt_screen(&self) -> bool {
+        self.alt_screen
+    }
+
+    pub fn cursor_shown(&self) -> bool {
+        self.cursor_shown
+    }
+
+    ///The depth given to managers created with this backend, true color unless set.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    ///Panics if the characters on the screen don't match the expected lines.
+    pub fn assert_chars(&self, expected: &str) {
+        assert_chars(&self.buffer, expected)
+    }
+
+    ///Panics if the colors on the screen don't match the expected lines.
+    pub fn assert_colors(&self, expected: &str, colors: &[(char, ColorSet)]) {
+        assert_colors(&self.buffer, expected, colors)
+    }
+}
+
+impl Backend for TestBackend {
+    fn size(&mut self) -> Result<Coord, ErrorKind> {
+        Ok(self.buffer.size())
+    }
+
+    fn move_to(&mut self, pos: Coord) -> Result<(), ErrorKind> {
+        self.cursor = pos;
+        Ok(())
+    }
+
+    fn set_fg(&mut self, color: Color) -> Result<(), ErrorKind> {
+        self.fg = color;
+        Ok(())
+    }
+
+    fn set_bg(&mut self, color: Color) -> Result<(), ErrorKind> {
+        self.bg = color;
+        Ok(())
+    }
+
+    fn set_attribute(&mut self, attribute: Attribute) -> Result<(), ErrorKind> {
+        if attribute == Attribute::Reset {
+            return self.reset_color()
+        }
+
+        if ATTRIBUTES.iter().any(|(on, _)| *on == attribute) {
+            self.attributes.set(attribute);
+        }
+        else {
+            for (on, off) in ATTRIBUTES {
+                if *off == attribute { self.attributes.unset(*on) }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reset_color(&mut self) -> Result<(), ErrorKind> {
+        self.fg = Color::Reset;
+        self.bg = Color::Reset;
+        self.attributes = Attributes::default();
+        Ok(())
+    }
+
+    fn print(&mut self, c: char) -> Result<(), ErrorKind> {
+        let size = self.buffer.size();
+
+        self.printed = None;
+
+        if self.cursor.x < size.x && self.cursor.y < size.y {
+            self.buffer.set(self.cursor, Pixel::Opaque(PixelData { attributes: self.attributes, ..PixelData::new(c, self.fg, self.bg) }));
+            self.printed = Some(self.cursor);
+        }
+        self.cursor.x += char_width(c);
+
+        Ok(())
+    }
+
+    fn print_marks(&mut self, marks: &str) -> Result<(), ErrorKind> {
+        let pos = match self.printed {
+            Some(pos) => pos,
+            None      => return Ok(()),
+        };
+
+        if let Pixel::Opaque(mut data) = self.buffer.get(pos) {
+            let width = data.width();
+            data.marks = Marks::new(&format!("{}{}", data.marks.as_str(), marks));
+            self.buffer.set(pos, Pixel::Opaque(data));
+
+            //the cursor moves past the rest of the cluster if it made the cell wider.
+            self.cursor.x += data.width() - width;
+        }
+
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> Result<(), ErrorKind> {
+        let size = self.buffer.size();
+
+        self.cursor.x = 0;
+
+        if self.cursor.y + 1 < size.y {
+            self.cursor.y += 1;
+            return Ok(())
+        }
+
+        //scroll everything up a line.
+        let mut scrolled = Vec2D::new(size);
+        for pos in CoordIter::new(Coord{x: 0, y: 1}, size) {
+            scrolled.set(pos - Coord{x: 0, y: 1}, self.buffer.get(pos));
+        }
+        self.buffer = scrolled;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self, show: bool) -> Result<(), ErrorKind> {
+        self.cursor_shown = show;
+        Ok(())
+    }
+
+    fn set_alt_screen(&mut self, alt: bool) -> Result<(), ErrorKind> {
+        self.alt_screen = alt;
+        Ok(())
+    }
+
+    ///Nothing can show up while waiting so this only waits if there is nothing queued.
+    fn poll_event(&mut self, duration: Duration) -> Result<bool, ErrorKind> {
+        if self.events.is_empty() {
+            std::thread::sleep(duration);
+            return Ok(false)
+        }
+
+        Ok(true)
+    }
+
+    ///Errors instead of waiting forever when nothing is queued.
+    fn read_event(&mut self) -> Result<Event, ErrorKind> {
+        self.events.pop_front().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no test events queued"))
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    fn cursor_position(&mut self) -> Result<Coord, ErrorKind> {
+        Ok(self.cursor)
+    }
+}
+
+///Returns the characters of the buffer one line per row, clear pixels are shown as spaces.
+///The right halves of wide characters are skipped so rows line up the way they would in a terminal.
+pub fn buffer_chars(buf: &Vec2D) -> String {
+    let size = buf.size();
+    let mut out = String::new();
+
+    for y in 0..size.y {
+        if y != 0 { out.push('\n') }
+
+        for x in 0..size.x {
+            match buf.get(Coord{x, y}) {
+                Pixel::Opaque(data) => {
+                    out.push(data.character);
+                    out.push_str(data.marks.as_str());
+                }
+                Pixel::Clear        => out.push(' '),
+                Pixel::Continuation => {}
+                Pixel::Blend(_)     => unreachable!("blended pixels are resolved when they are set"),
+            }
+        }
+    }
+
+    out
+}
+
+///Splits up the expected screen, a leading new line is ignored so the rows can start on their own line.
+fn expected_lines(expected: &str) -> Vec<&str> {
+    expected.strip_prefix('\n').unwrap_or(expected).split('\n').collect()
+}
+
+///Panics if the characters of the buffer don't match the expected lines, clear pixels are expected as spaces.
+pub fn assert_chars(buf: &Vec2D, expected: &str) {
+    let expected = expected_lines(expected).join("\n");
+    let found = buffer_chars(buf);
+
+    assert!(found == expected, "screen does not match.\nexpected:\n{}\nfound:\n{}\n", expected, found);
+}
+
+///Panics if the colors of the buffer don't match the expected lines.
+///Each character of expected is looked up in colors, a space skips the check for that cell.
+///Unlike assert_chars every cell gets a character here, the right halves of wide characters can be skipped with a space.
+pub fn assert_colors(buf: &Vec2D, expected: &str, colors: &[(char, ColorSet)]) {
+    let lines = expected_lines(expected);
+    let size = buf.size();
+
+    assert_eq!(lines.len() as i32, size.y, "expected {} rows found {}", lines.len(), size.y);
+
+    for (y, line) in lines.iter().enumerate() {
+        assert_eq!(line.chars().count() as i32, size.x, "row {} expected {} columns found {}", y, line.chars().count(), size.x);
+
+        for (x, key) in line.chars().enumerate() {
+            if key == ' ' { continue }
+
+            let colors = match colors.iter().find(|(k, _)| *k == key) {
+                Some((_, colors)) => colors,
+                None => panic!("no colors given for '{}'", key),
+            };
+
+            let pos = Coord{x: x as i32, y: y as i32};
+            match buf.get(pos) {
+                Pixel::Opaque(data) => {
+                    assert!(
+                        data.get_color_set() == *colors,
+                        "colors at {:?} expected {:?} found {:?}", pos, colors, data.get_color_set()
+                    );
+                }
+                Pixel::Clear => panic!("expected colors at {:?} found a clear pixel", pos),
+                Pixel::Continuation => panic!("expected colors at {:?} found the right half of a wide character", pos),
+                Pixel::Blend(_)     => unreachable!("blended pixels are resolved when they are set"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Manager, Viewport};
+    use crate::frame_types::{fill, text};
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    #[test]
+    fn draw_and_resize() {
+        let mut manager = Manager::with_backend(TestBackend::new(Coord{x: 4, y: 2})).unwrap();
+        let root = fill::new(Pixel::new('#', Color::Red, Color::Black));
+
+        manager.draw(root.clone()).unwrap();
+        assert!(manager.backend().alt_screen());
+        manager.backend().assert_chars("
+####
+####");
+
+        manager.backend_mut().resize(Coord{x: 2, y: 3});
+        assert!(manager.poll_input(Duration::from_secs(0)).unwrap().is_none());
+        assert_eq!(manager.size(), Coord{x: 2, y: 3});
+
+        manager.draw(root).unwrap();
+        manager.backend().assert_chars("
+##
+##
+##");
+    }
+
+    #[test]
+    fn text_colors() {
+        let red   = ColorSet::new(Color::Red, Color::Black).with(Attribute::Bold);
+        let white = ColorSet::new(Color::Rgb{r: 255, g: 255, b: 255}, Color::Rgb{r: 0, g: 0, b: 0});
+
+        let mut manager = Manager::with_backend(TestBackend::new(Coord{x: 5, y: 2})).unwrap();
+        let root = text::new();
+        {
+            let mut temp = root.borrow_mut();
+            temp.entries.push_back(text::Entry::new_color("abc", red));
+            temp.entries.push_back(text::Entry::new("de"));
+        }
+
+        manager.draw(root).unwrap();
+
+        manager.backend().assert_chars("abc  \nde   ");
+        manager.backend().assert_colors("rrr  \nwwwww", &[('r', red), ('w', white)]);
+    }
+
+    #[test]
+    fn color_depth() {
+        let orange = ColorSet::new(Color::Rgb{r: 255, g: 135, b: 0}, Color::Rgb{r: 0, g: 0, b: 0});
+
+        let mut backend = TestBackend::new(Coord{x: 1, y: 1});
+        backend.set_color_depth(ColorDepth::Ansi16);
+
+        let mut manager = Manager::with_backend(backend).unwrap();
+        let root = fill::new(Pixel::new_color_set('#', orange));
+
+        manager.draw(root.clone()).unwrap();
+        manager.backend().assert_colors("o", &[('o', ColorSet::new(Color::Yellow, Color::Black))]);
+
+        manager.set_color_depth(ColorDepth::Ansi256);
+        manager.draw(root).unwrap();
+        manager.backend().assert_colors("o", &[('o', ColorSet::new(Color::AnsiValue(208), Color::AnsiValue(16)))]);
+    }
+
+    #[test]
+    fn inline() {
+        let mut backend = TestBackend::new(Coord{x: 4, y: 4});
+        for c in ['a', 'b', 'c'] {
+            backend.print(c).unwrap();
+            backend.new_line().unwrap();
+        }
+        //something already on the cursors line pushes the viewport to the next one.
+        backend.print('$').unwrap();
+
+        let mut manager = Manager::with_viewport(backend, Viewport::Inline(2)).unwrap();
+        assert!(!manager.backend().alt_screen());
+        assert_eq!(manager.size(), Coord{x: 4, y: 2});
+
+        manager.draw(fill::new(Pixel::new('#', Color::Red, Color::Black))).unwrap();
+        manager.backend().assert_chars("
+c   
+$   
+####
+####");
+        assert_eq!(manager.to_viewport(1, 3), Coord{x: 1, y: 1});
+
+        //the viewport moves up to stay on screen.
+        manager.backend_mut().resize(Coord{x: 3, y: 3});
+        manager.poll_input(Duration::from_secs(0)).unwrap();
+        manager.draw(fill::new(Pixel::new('%', Color::Red, Color::Black))).unwrap();
+        manager.backend().assert_chars("
+   
+%%%
+%%%");
+    }
+
+    #[test]
+    fn injected_input() {
+        let mut manager = Manager::with_backend(TestBackend::new(Coord{x: 1, y: 1})).unwrap();
+
+        manager.backend_mut().push_input(Input::KeyBoard(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+        manager.backend_mut().push_input(Input::Paste("hi".to_string()));
+
+        match manager.get_input().unwrap() {
+            Input::KeyBoard(e) => assert_eq!(e.code, KeyCode::Enter),
+            _ => panic!("expected a key"),
+        }
+
+        match manager.get_input().unwrap() {
+            Input::Paste(s) => assert_eq!(s, "hi"),
+            _ => panic!("expected a paste"),
+        }
+    }
+}
@@ -13,6 +13,11 @@ impl Vec2D {
         }
     }
 
+    ///Sets every pixel back to clear.
+    pub fn clear(&mut self) {
+        self.values.fill(Pixel::Clear);
+    }
+
     pub fn set_size(&mut self, size: Coord) {
         self.size = size;
 
@@ -28,8 +33,70 @@ impl Vec2D {
         }
     }
 
-    pub fn set(&mut self, index: Coord, value: Pixel) {
-        self.values[((index.y * self.size.x) + index.x) as usize] = value;
+    /// Sets the pixel, a wide character also takes the cell to its right.
+    /// Overwriting half of a wide character leaves the other half as a space.
+    /// Blended pixels are combined with the pixel already there, clear pixels count as a space in the terminals colors.
+    pub fn set(&mut self, index: Coord, mut value: Pixel) {
+        if let Pixel::Blend(blend) = value {
+            let under = match self.get(index) {
+                Pixel::Opaque(data) => data,
+                //the wide character was already blended along with its left half.
+                Pixel::Continuation if blend.character.is_none() => return,
+                _ => PixelData::new(' ', Color::Reset, Color::Reset),
+            };
+
+            value = Pixel::Opaque(blend.over(under));
+        }
+
+        self.break_wide(index);
+
+        if let Pixel::Opaque(mut data) = value {
+            if data.width() == 2 {
+                let next = Coord{x: index.x + 1, y: index.y};
+
+                if next.x < self.size.x {
+                    self.break_wide(next);
+                    let i = self.flat(next);
+                    self.values[i] = Pixel::Continuation;
+                }
+                else {
+                    //no room for the right half.
+                    data = data.with_char(' ');
+                }
+
+                let i = self.flat(index);
+                self.values[i] = Pixel::Opaque(data);
+                return
+            }
+        }
+
+        let i = self.flat(index);
+        self.values[i] = value;
+    }
+
+    /// If the cell is part of a wide character replaces the other half with a space.
+    fn break_wide(&mut self, index: Coord) {
+        match self.get(index) {
+            Pixel::Continuation if index.x > 0 => {
+                let lead = self.flat(Coord{x: index.x - 1, y: index.y});
+
+                if let Pixel::Opaque(data) = &mut self.values[lead] {
+                    *data = data.with_char(' ');
+                }
+            }
+            Pixel::Opaque(data) if data.width() == 2 && index.x + 1 < self.size.x => {
+                let next = self.flat(Coord{x: index.x + 1, y: index.y});
+
+                if self.values[next] == Pixel::Continuation {
+                    self.values[next] = Pixel::Opaque(data.with_char(' '));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn flat(&self, index: Coord) -> usize {
+        ((index.y * self.size.x) + index.x) as usize
     }
 
     pub fn get_flat(&self, index: usize) -> Pixel {
@@ -37,7 +104,7 @@ impl Vec2D {
     }
 
     pub fn get(&self, index: Coord) -> Pixel {
-        self.values[((index.y * self.size.x) + index.x) as usize]
+        self.values[self.flat(index)]
     }
 
     pub fn size(&self) -> Coord {